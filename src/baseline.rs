@@ -0,0 +1,405 @@
+use crate::evaluator::{ErrorEvalResult, EvalRecord, EvalReport};
+use crate::file_lock::write_atomic;
+use anyhow::{anyhow, bail, Context, Result};
+use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
+use serde::Deserialize;
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Directory (relative to the bench root) holding saved eval baselines.
+/// It is hidden so `process_benchmarks` doesn't mistake it for a repo.
+const BASELINES_DIR: &str = ".baselines";
+
+/// Returns the path a baseline named `name` is stored at.
+pub fn baseline_path(bench_root: &Path, name: &str) -> PathBuf {
+    bench_root
+        .join(BASELINES_DIR)
+        .join(format!("{}.json", name))
+}
+
+/// Saves the eval records of a run as the baseline `name`, overwriting any
+/// previous baseline with that name.
+pub fn save_baseline(bench_root: &Path, name: &str, records: &[EvalRecord]) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        bail!("Invalid baseline name {:?}: must be non-empty and contain no path separators", name);
+    }
+    let path = baseline_path(bench_root, name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create baselines directory: {:?}", parent))?;
+    }
+    let json = serde_json::to_string_pretty(records).context("Failed to serialize baseline")?;
+    write_atomic(&path, json).with_context(|| format!("Failed to write baseline to {:?}", path))?;
+    Ok(path)
+}
+
+/// Either a JSON eval report or a saved baseline (just the records).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RunFile {
+    Report(EvalReport),
+    Records(Vec<EvalRecord>),
+}
+
+/// Loads an eval run. `name_or_path` is either the path to a JSON eval report
+/// (as written by `eval --output-format json`) or the name of a saved baseline.
+pub fn load_run(bench_root: &Path, name_or_path: &str) -> Result<Vec<EvalRecord>> {
+    let direct_path = PathBuf::from(name_or_path);
+    let path = if direct_path.is_file() {
+        direct_path
+    } else {
+        let path = baseline_path(bench_root, name_or_path);
+        if !path.is_file() {
+            return Err(anyhow!(
+                "No eval run found at {:?} and no baseline named {:?} (looked for {:?})",
+                name_or_path,
+                name_or_path,
+                path
+            ));
+        }
+        path
+    };
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read eval run: {:?}", path))?;
+    let run: RunFile = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse eval run: {:?}", path))?;
+    Ok(match run {
+        RunFile::Report(report) => report.results,
+        RunFile::Records(records) => records,
+    })
+}
+
+/// Coarse classification of how well an error was localized, ordered from
+/// worst to best so that a decrease is a regression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EvalStatus {
+    /// Every fix was fully incorrect
+    Incorrect,
+    /// No fix was fully correct, but at least one was partially correct
+    Partial,
+    /// At least one fix was fully correct
+    Correct,
+}
+
+impl fmt::Display for EvalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalStatus::Incorrect => write!(f, "incorrect"),
+            EvalStatus::Partial => write!(f, "partial"),
+            EvalStatus::Correct => write!(f, "correct"),
+        }
+    }
+}
+
+/// Classifies an error using the blame spans. Returns `None` if there was
+/// nothing to classify (e.g. no fixes or only fixes without fix lines).
+fn status(result: &ErrorEvalResult) -> Option<EvalStatus> {
+    if result.is_any_fix_fully_correct() {
+        Some(EvalStatus::Correct)
+    } else if result.is_every_fix_fully_incorrect() {
+        Some(EvalStatus::Incorrect)
+    } else if result.fix_evals.iter().any(|eval| eval.is_partially_correct()) {
+        Some(EvalStatus::Partial)
+    } else {
+        None
+    }
+}
+
+/// Classifies an error using all binders.
+fn status_all_binders(result: &ErrorEvalResult) -> Option<EvalStatus> {
+    if result.is_any_fix_fully_correct_all_binders() {
+        Some(EvalStatus::Correct)
+    } else if result.is_every_fix_fully_incorrect_all_binders() {
+        Some(EvalStatus::Incorrect)
+    } else if result
+        .fix_evals
+        .iter()
+        .any(|eval| eval.is_partially_correct_all_binders())
+    {
+        Some(EvalStatus::Partial)
+    } else {
+        None
+    }
+}
+
+/// Identifies an error across runs.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ErrorKey {
+    pub repo_name: String,
+    pub subdir: PathBuf,
+    pub commit: String,
+    pub error_name: String,
+}
+
+impl ErrorKey {
+    /// Whether both keys are in the same suite (repo, subdir and commit).
+    fn same_suite(&self, other: &ErrorKey) -> bool {
+        (&self.repo_name, &self.subdir, &self.commit) == (&other.repo_name, &other.subdir, &other.commit)
+    }
+
+    fn of(record: &EvalRecord) -> Self {
+        Self {
+            repo_name: record.repo_name.clone(),
+            subdir: record.subdir.clone(),
+            commit: record.commit.clone(),
+            error_name: record.result.error_name.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ErrorKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.repo_name,
+            self.subdir.display(),
+            self.error_name
+        )
+    }
+}
+
+/// How a single error changed between two runs.
+#[derive(Debug, Clone)]
+pub struct ErrorDiff {
+    /// The key of the error in the new run
+    pub key: ErrorKey,
+    /// The error's name in the old run, if it was renamed
+    pub old_error_name: Option<String>,
+    pub old_status: Option<EvalStatus>,
+    pub new_status: Option<EvalStatus>,
+    pub old_status_all_binders: Option<EvalStatus>,
+    pub new_status_all_binders: Option<EvalStatus>,
+    pub old_best_ratio: f64,
+    pub new_best_ratio: f64,
+    pub old_best_ratio_all_binders: f64,
+    pub new_best_ratio_all_binders: f64,
+}
+
+impl ErrorDiff {
+    /// A regression is a drop in status or best ratio (blamed or all binders).
+    pub fn is_regression(&self) -> bool {
+        self.new_status < self.old_status
+            || self.new_status_all_binders < self.old_status_all_binders
+            || self.new_best_ratio < self.old_best_ratio
+            || self.new_best_ratio_all_binders < self.old_best_ratio_all_binders
+    }
+
+    pub fn is_improvement(&self) -> bool {
+        !self.is_regression()
+            && (self.new_status > self.old_status
+                || self.new_status_all_binders > self.old_status_all_binders
+                || self.new_best_ratio > self.old_best_ratio
+                || self.new_best_ratio_all_binders > self.old_best_ratio_all_binders)
+    }
+}
+
+/// The result of comparing an old (baseline) run against a new one.
+#[derive(Debug, Default)]
+pub struct RunComparison {
+    /// Errors present in both runs whose results changed
+    pub changed: Vec<ErrorDiff>,
+    /// Number of errors present in both runs with identical results
+    pub num_unchanged: usize,
+    /// Errors only in the old run (flux no longer reports them)
+    pub removed: Vec<ErrorKey>,
+    /// Errors only in the new run
+    pub added: Vec<ErrorKey>,
+}
+
+impl RunComparison {
+    /// Number of errors present in both runs whose results got worse. Errors
+    /// missing from the new run are counted separately, in `removed`.
+    pub fn num_regressions(&self) -> usize {
+        self.changed.iter().filter(|diff| diff.is_regression()).count()
+    }
+}
+
+/// Matches the `old` records with the `new` ones of the same suite, one to
+/// one. Records are matched by fingerprint where both have one and it is
+/// unambiguous within the suite, so renamed errors are still compared with
+/// themselves, and by error name otherwise.
+///
+/// # Returns
+/// * For each record in `old`, the index of its match in `new`, if any.
+fn match_records(old: &[EvalRecord], new: &[EvalRecord]) -> Vec<Option<usize>> {
+    let old_keys: Vec<ErrorKey> = old.iter().map(ErrorKey::of).collect();
+    let new_keys: Vec<ErrorKey> = new.iter().map(ErrorKey::of).collect();
+    // Indices of the records in the suite of `key` with `fingerprint`
+    fn with_fingerprint(records: &[EvalRecord], keys: &[ErrorKey], key: &ErrorKey, fingerprint: &str) -> Vec<usize> {
+        (0..records.len())
+            .filter(|&idx| keys[idx].same_suite(key) && records[idx].fingerprint.as_deref() == Some(fingerprint))
+            .collect()
+    }
+
+    let mut matches = vec![None; old.len()];
+    let mut used = vec![false; new.len()];
+
+    // 1. Fingerprints that identify exactly one error of the suite in both runs
+    for (old_idx, record) in old.iter().enumerate() {
+        let Some(fingerprint) = &record.fingerprint else { continue };
+        let key = &old_keys[old_idx];
+        if with_fingerprint(old, &old_keys, key, fingerprint).len() != 1 {
+            continue;
+        }
+        if let [new_idx] = with_fingerprint(new, &new_keys, key, fingerprint)[..] {
+            matches[old_idx] = Some(new_idx);
+            used[new_idx] = true;
+        }
+    }
+
+    // 2. Fall back to the name for the rest
+    for (old_idx, key) in old_keys.iter().enumerate() {
+        if matches[old_idx].is_some() {
+            continue;
+        }
+        if let Some(new_idx) = (0..new.len()).find(|&idx| !used[idx] && new_keys[idx] == *key) {
+            matches[old_idx] = Some(new_idx);
+            used[new_idx] = true;
+        }
+    }
+
+    matches
+}
+
+/// Compares two eval runs error by error.
+pub fn compare_runs(old: &[EvalRecord], new: &[EvalRecord]) -> RunComparison {
+    let matches = match_records(old, new);
+
+    let mut comparison = RunComparison::default();
+    for (old_record, new_idx) in old.iter().zip(&matches) {
+        let old_key = ErrorKey::of(old_record);
+        let Some(new_idx) = new_idx else {
+            comparison.removed.push(old_key);
+            continue;
+        };
+        let new_record = &new[*new_idx];
+        let key = ErrorKey::of(new_record);
+        let (old_result, new_result) = (&old_record.result, &new_record.result);
+        let diff = ErrorDiff {
+            old_error_name: (old_key.error_name != key.error_name).then_some(old_key.error_name),
+            key,
+            old_status: status(old_result),
+            new_status: status(new_result),
+            old_status_all_binders: status_all_binders(old_result),
+            new_status_all_binders: status_all_binders(new_result),
+            old_best_ratio: old_result.best_ratio(),
+            new_best_ratio: new_result.best_ratio(),
+            old_best_ratio_all_binders: old_result.best_ratio_all_binders(),
+            new_best_ratio_all_binders: new_result.best_ratio_all_binders(),
+        };
+        if diff.is_regression() || diff.is_improvement() {
+            comparison.changed.push(diff);
+        } else {
+            comparison.num_unchanged += 1;
+        }
+    }
+    comparison.added = (0..new.len())
+        .filter(|idx| !matches.contains(&Some(*idx)))
+        .map(|idx| ErrorKey::of(&new[idx]))
+        .collect();
+    comparison.removed.sort();
+    comparison.added.sort();
+    comparison.changed.sort_by(|a, b| a.key.cmp(&b.key));
+    comparison
+}
+
+fn format_status(status: Option<EvalStatus>) -> String {
+    status.map_or("N/A".to_string(), |s| s.to_string())
+}
+
+fn format_ratio_change(old: f64, new: f64) -> String {
+    if old == new {
+        format!("{:.2}", new)
+    } else {
+        format!("{:.2} -> {:.2} ({:+.2})", old, new, new - old)
+    }
+}
+
+/// Generates a report of the changed, added and removed errors.
+pub fn generate_comparison_report(comparison: &RunComparison) -> String {
+    let mut sections = Vec::new();
+
+    if !comparison.changed.is_empty() {
+        let mut table = Table::new();
+        table
+            .load_preset(comfy_table::presets::UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                "Error",
+                "Change",
+                "Status\n(Blamed)",
+                "Status\n(All)",
+                "Best Ratio\n(Blamed)",
+                "Best Ratio\n(All)",
+            ]);
+        for diff in &comparison.changed {
+            let change = if diff.is_regression() { "❌ regressed" } else { "✅ improved" };
+            let error = match &diff.old_error_name {
+                Some(old_error_name) => format!("{} (was {})", diff.key, old_error_name),
+                None => diff.key.to_string(),
+            };
+            table.add_row(vec![
+                Cell::new(error),
+                Cell::new(change),
+                Cell::new(format!(
+                    "{} -> {}",
+                    format_status(diff.old_status),
+                    format_status(diff.new_status)
+                )),
+                Cell::new(format!(
+                    "{} -> {}",
+                    format_status(diff.old_status_all_binders),
+                    format_status(diff.new_status_all_binders)
+                )),
+                Cell::new(format_ratio_change(diff.old_best_ratio, diff.new_best_ratio))
+                    .set_alignment(CellAlignment::Right),
+                Cell::new(format_ratio_change(
+                    diff.old_best_ratio_all_binders,
+                    diff.new_best_ratio_all_binders,
+                ))
+                .set_alignment(CellAlignment::Right),
+            ]);
+        }
+        sections.push(table.to_string());
+    }
+
+    if !comparison.removed.is_empty() {
+        let mut section = "Errors missing from the new run:".to_string();
+        for key in &comparison.removed {
+            section.push_str(&format!("\n  - {}", key));
+        }
+        sections.push(section);
+    }
+
+    if !comparison.added.is_empty() {
+        let mut section = "Errors only in the new run:".to_string();
+        for key in &comparison.added {
+            section.push_str(&format!("\n  + {}", key));
+        }
+        sections.push(section);
+    }
+
+    let num_improvements = comparison
+        .changed
+        .iter()
+        .filter(|diff| diff.is_improvement())
+        .count();
+    sections.push(format!(
+        "--- Comparison Summary ---\n\
+        Regressions:  {}\n\
+        Improvements: {}\n\
+        Unchanged:    {}\n\
+        Removed:      {}\n\
+        Added:        {}",
+        comparison.num_regressions(),
+        num_improvements,
+        comparison.num_unchanged,
+        comparison.removed.len(),
+        comparison.added.len(),
+    ));
+
+    sections.join("\n\n")
+}
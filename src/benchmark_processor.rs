@@ -1,57 +1,137 @@
 use crate::benchmark_suite::BenchmarkSuite;
 use crate::cached_repository::{CachedRepository, GitWorktreeDir}; // Updated import
 use crate::local_paths::LocalPathResolver;
-use anyhow::{Context, Result};
+use crate::types::ErrorAndFixes;
+use anyhow::{anyhow, Context, Result};
 use clap::Parser;
+use regex::Regex;
 use std::ffi::OsStr;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// A pattern matching repo names, commit hashes or error names. Patterns are
+/// globs (`*` and `?` are wildcards) unless prefixed with `re:`, in which
+/// case the rest is a regex that has to match somewhere in the name.
+#[derive(Clone, Debug)]
+pub struct NamePattern {
+    source: String,
+    regex: Regex,
+    is_glob: bool,
+}
+
+impl NamePattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let (regex, is_glob) = match pattern.strip_prefix("re:") {
+            Some(regex) => (regex.to_string(), false),
+            None => (Self::glob_to_regex(pattern), true),
+        };
+        Ok(Self {
+            source: pattern.to_string(),
+            regex: Regex::new(&regex).map_err(|e| format!("Invalid pattern {:?}: {}", pattern, e))?,
+            is_glob,
+        })
+    }
+
+    fn glob_to_regex(glob: &str) -> String {
+        let mut regex = String::from("^");
+        for c in glob.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        regex
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+
+    /// Like `matches`, but a glob also matches commit hashes it is a prefix
+    /// of, so that the short hashes in error names select their suite.
+    pub fn matches_commit(&self, commit_hash: &str) -> bool {
+        if self.is_glob {
+            (1..=commit_hash.len()).any(|end| self.regex.is_match(&commit_hash[..end]))
+        } else {
+            self.regex.is_match(commit_hash)
+        }
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
 
 #[derive(Parser, Clone, Debug)]
 pub struct BenchmarkArgs {
-    /// The repos to run on (if empty, runs on all) - Matches directory names in bench_root
+    /// The repos to run on (if empty, runs on all) - Globs (or `re:` regexes) matching directory names in bench_root
+    #[arg(long, value_parser = NamePattern::parse)]
+    pub repos: Vec<NamePattern>,
+    /// The commits to run on (if empty, runs on all) - Globs (or `re:` regexes); a glob also matches hashes it is a prefix of
+    #[arg(long, value_parser = NamePattern::parse)]
+    pub commits: Vec<NamePattern>,
+    /// The errors to run on (if empty, runs on all in chosen suites) - Globs (or `re:` regexes) matching the error_name field
+    #[arg(long, value_parser = NamePattern::parse)]
+    pub errors: Vec<NamePattern>,
+    /// Only benchmarks without any annotated fix lines
     #[arg(long)]
-    pub repos: Vec<String>,
-    /// The commits to run on (if empty, runs on all - exact match)
+    pub unannotated: bool,
+    /// Only benchmarks with a fix marked as trivial
+    #[arg(long, conflicts_with = "non_trivial")]
+    pub trivial: bool,
+    /// Only benchmarks with a fix marked as non-trivial
     #[arg(long)]
-    pub commits: Vec<String>,
-    /// The errors to run on (if empty, runs on all in chosen suites) - Matches error_name field
+    pub non_trivial: bool,
+    /// Only benchmarks with a fix that has a note
     #[arg(long)]
-    pub errors: Vec<String>,
+    pub has_note: bool,
+    /// Only benchmarks with a fix of at least N fix lines
+    #[arg(long, value_name = "N")]
+    pub min_fix_lines: Option<usize>,
 }
 
+impl BenchmarkArgs {
+    /// Whether any of the filters apply to individual benchmarks (rather than
+    /// to whole suites).
+    pub fn filters_benchmarks(&self) -> bool {
+        !self.errors.is_empty()
+            || self.unannotated
+            || self.trivial
+            || self.non_trivial
+            || self.has_note
+            || self.min_fix_lines.is_some()
+    }
 
-/// Iterates through benchmark suites based on filter criteria and executes an action.
+    /// Whether `benchmark` passes the error name and annotation filters.
+    pub fn matches_benchmark(&self, benchmark: &ErrorAndFixes) -> bool {
+        let fixes = &benchmark.fixes;
+        (self.errors.is_empty() || self.errors.iter().any(|p| p.matches(&benchmark.error_name)))
+            && (!self.unannotated || fixes.iter().all(|fix| fix.fix_lines.is_empty()))
+            && (!self.trivial || fixes.iter().any(|fix| fix.is_trivial == Some(true)))
+            && (!self.non_trivial || fixes.iter().any(|fix| fix.is_trivial == Some(false)))
+            && (!self.has_note || fixes.iter().any(|fix| fix.note.is_some()))
+            && self
+                .min_fix_lines
+                .is_none_or(|min| fixes.iter().any(|fix| fix.fix_lines.len() >= min))
+    }
+}
+
+/// Walks the benchmark tree and returns every suite matching the repo and
+/// commit filters, sorted by suite path so that processing order is stable.
 ///
 /// # Arguments
 /// * `benchmarks_root`: The root directory containing all benchmark suites.
-/// * `filters`: Criteria to select which benchmarks to process (repo names, commits).
-/// * `local_resolver`: Used to find local source code overrides.
-/// * `cache_root`: The directory used for caching Git repositories.
-/// * `use_cache`: Preference for using cached worktrees (true) or temporary ones (false).
-/// * `action`: A closure to execute for each matching benchmark suite.
-///             It receives the `BenchmarkSuite` and the `GitWorktreeDir`.
-pub fn process_benchmarks<F>(
-    benchmarks_root: &Path,
-    filters: &BenchmarkArgs, // Pass by reference
-    local_resolver: &LocalPathResolver, // Pass by reference
-    cache_root: &Path,
-    use_cache: bool, // Added cache preference flag
-    mut action: F, // Use FnMut to allow modification of captured state if needed
-) -> Result<()>
-where
-    F: FnMut(&BenchmarkSuite, &GitWorktreeDir) -> Result<()>, // Updated type
-{
-    println!(
-        "Searching for benchmarks in: {:?}",
-        benchmarks_root.canonicalize().unwrap_or_else(|_| benchmarks_root.to_path_buf())
-    );
-    println!("Applying filters: {:?}", filters);
-    println!("Worktree cache preference: {}", if use_cache { "Cached" } else { "Temporary" });
-
-
-    let cache_root = cache_root.canonicalize()?;
-    let cache_repo = CachedRepository::new(cache_root.to_path_buf(), local_resolver);
+/// * `filters`: Criteria to select which suites to return (repo names, commits).
+pub fn discover_suites(benchmarks_root: &Path, filters: &BenchmarkArgs) -> Result<Vec<BenchmarkSuite>> {
+    let mut suites = Vec::new();
 
     // 1. Iterate through repository directories
     for repo_entry_res in fs::read_dir(benchmarks_root).with_context(|| {
@@ -80,8 +160,13 @@ where
             }
         };
 
+        // Skip hidden directories (e.g. saved eval baselines)
+        if repo_name.starts_with('.') {
+            continue;
+        }
+
         // Apply repo name filter
-        if !filters.repos.is_empty() && !filters.repos.contains(&repo_name) {
+        if !filters.repos.is_empty() && !filters.repos.iter().any(|p| p.matches(&repo_name)) {
             continue;
         }
 
@@ -139,12 +224,14 @@ where
                 };
 
                 // Apply commit hash filter
-                if !filters.commits.is_empty() && !filters.commits.contains(&commit_hash) {
+                if !filters.commits.is_empty()
+                    && !filters.commits.iter().any(|p| p.matches_commit(&commit_hash))
+                {
                     continue;
                 }
 
                 // 4. Found a matching suite directory, now process it
-                println!(
+                eprintln!(
                     "Found potential suite: {} / {} / {}",
                     repo_name,
                     subdir_name_os.to_string_lossy(),
@@ -166,55 +253,329 @@ where
                      }
                 };
 
-                // We NEED GitInformation to resolve the repository source code
-                let git_info = match suite.git_info() {
-                    Some(info) => info,
-                    None => {
-                        eprintln!(
-                            "Warning: Missing git-info.json in suite {:?}. Cannot resolve source code. Skipping.",
-                            suite.path()
-                        );
-                        continue;
-                    }
-                };
+                suites.push(suite);
+            } // End commit loop
+        } // End subdir loop
+    } // End repo loop
 
-                // 5. Resolve the code repository path (local or cached, temp or persistent)
-                println!("Resolving code for {}@{}...", repo_name, &commit_hash[..7]);
-                let worktree = match cache_repo.get_worktree(
-                    &repo_name,
-                    &commit_hash,
-                    &git_info.remote, // Pass remote info
-                    use_cache,       // Pass cache preference
-                ) {
-                    Ok(wt) => wt,
-                    Err(e) => {
-                         // Log non-fatal error and skip
-                         eprintln!(
-                            "Warning: Failed to get worktree for suite {:?}: {}. Skipping.",
-                            suite.path(), e
-                        );
-                        continue; // Skip this suite if code can't be resolved
-                    }
-                };
-                 println!("Code worktree ready at: {:?}", worktree.path());
+    suites.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(suites)
+}
 
+/// The stage of processing a suite at which it failed.
+///
+/// Actions can attribute their errors to a stage by attaching it as context,
+/// e.g. `run_flux(..).context(FailureStage::FluxRun)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureStage {
+    /// The suite has no `git-info.json`, so its source code can't be resolved
+    GitInfo,
+    /// The worktree for the suite's commit couldn't be checked out
+    Worktree,
+    /// The suite's benchmark files couldn't be loaded
+    LoadBenchmarks,
+    /// Running `cargo flux` failed
+    FluxRun,
+    /// The constraint debug info emitted by flux couldn't be parsed
+    DebugInfoParse,
+    /// The action failed without attributing the failure to a stage
+    Action,
+}
 
-                // 6. Execute the provided action
-                if let Err(e) = action(&suite, &worktree) {
-                     // Propagate the first error encountered.
-                     // Consider collecting errors if you want to process all possible suites.
-                     return Err(e).with_context(|| format!("Action failed for suite {:?}", suite.path()));
-                     // Or just print warning and continue:
-                     // eprintln!("Warning: Action failed for suite {:?}: {}. Continuing.", suite.path(), e);
-                }
+impl fmt::Display for FailureStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FailureStage::GitInfo => "git-info",
+            FailureStage::Worktree => "worktree",
+            FailureStage::LoadBenchmarks => "load benchmarks",
+            FailureStage::FluxRun => "flux run",
+            FailureStage::DebugInfoParse => "debug-info parse",
+            FailureStage::Action => "action",
+        };
+        write!(f, "{}", name)
+    }
+}
 
-                // worktree (GitWorktreeDir) automatically cleans up temporary worktrees when it goes out of scope here
-                println!("Finished processing suite: {:?}.", suite.path());
+/// A failure encountered while processing a suite.
+#[derive(Debug)]
+pub struct SuiteFailure {
+    pub suite_path: PathBuf,
+    /// The benchmark being processed, if the failure is specific to one
+    pub error_name: Option<String>,
+    pub stage: FailureStage,
+    pub error: anyhow::Error,
+}
 
-            } // End commit loop
-        } // End subdir loop
-    } // End repo loop
+impl SuiteFailure {
+    /// Creates a failure, taking the stage from the error's context if it has
+    /// one and falling back to `default_stage` otherwise.
+    pub fn new(suite_path: &Path, default_stage: FailureStage, error: anyhow::Error) -> Self {
+        Self {
+            suite_path: suite_path.to_path_buf(),
+            error_name: None,
+            stage: error
+                .downcast_ref::<FailureStage>()
+                .copied()
+                .unwrap_or(default_stage),
+            error,
+        }
+    }
+}
 
-    println!("Finished processing all matching benchmarks.");
+/// Formats the failures as a report listing, per failure, the suite, the stage
+/// that failed and the full error chain.
+pub fn generate_failure_report(failures: &[SuiteFailure]) -> String {
+    let mut report = format!("--- Failure Report ({} Total) ---", failures.len());
+    for failure in failures {
+        report.push_str(&format!("\n[{}] {}", failure.stage, failure.suite_path.display()));
+        if let Some(error_name) = &failure.error_name {
+            report.push_str(&format!(" ({})", error_name));
+        }
+        // Skip the stage marker itself; it's already in the header
+        let stage_str = failure.stage.to_string();
+        let causes = failure
+            .error
+            .chain()
+            .map(|cause| cause.to_string())
+            .filter(|cause| *cause != stage_str);
+        for (i, cause) in causes.enumerate() {
+            if i == 0 {
+                report.push_str(&format!("\n    {}", cause));
+            } else {
+                report.push_str(&format!("\n      caused by: {}", cause));
+            }
+        }
+    }
+    report
+}
+
+/// Controls how `process_benchmarks` checks out and schedules suites.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessOptions {
+    /// Preference for using cached worktrees (true) or temporary ones (false).
+    pub use_cache: bool,
+    /// Maximum number of suites to process at the same time.
+    pub jobs: usize,
+    /// Whether to continue with the remaining suites when the action fails.
+    pub keep_going: bool,
+}
+
+/// Resolves the worktree for a single suite and runs `action` on it.
+fn process_suite<F>(
+    cache_repo: &CachedRepository,
+    suite: &BenchmarkSuite,
+    use_cache: bool,
+    action: &F,
+) -> Result<(), SuiteFailure>
+where
+    F: Fn(&BenchmarkSuite, &GitWorktreeDir) -> Result<()>,
+{
+    // We NEED GitInformation to resolve the repository source code
+    let git_info = match suite.git_info() {
+        Some(info) => info,
+        None => {
+            eprintln!(
+                "Warning: Missing git-info.json in suite {:?}. Cannot resolve source code. Skipping.",
+                suite.path()
+            );
+            return Err(SuiteFailure::new(
+                suite.path(),
+                FailureStage::GitInfo,
+                anyhow!("Missing git-info.json"),
+            ));
+        }
+    };
+
+    // Resolve the code repository path (local or cached, temp or persistent)
+    eprintln!("Resolving code for {}@{}...", suite.repo_name(), &suite.commit_hash()[..7]);
+    let worktree = match cache_repo.get_worktree(
+        suite.repo_name(),
+        suite.commit_hash(),
+        &git_info.remote, // Pass remote info
+        use_cache,        // Pass cache preference
+    ) {
+        Ok(wt) => wt,
+        Err(e) => {
+            // Log non-fatal error and skip
+            eprintln!(
+                "Warning: Failed to get worktree for suite {:?}: {}. Skipping.",
+                suite.path(), e
+            );
+            // Skip this suite if code can't be resolved
+            return Err(SuiteFailure::new(suite.path(), FailureStage::Worktree, e));
+        }
+    };
+    eprintln!("Code worktree ready at: {:?}", worktree.path());
+
+    // Execute the provided action
+    action(suite, &worktree)
+        .map_err(|e| SuiteFailure::new(suite.path(), FailureStage::Action, e))?;
+
+    // worktree (GitWorktreeDir) automatically cleans up temporary worktrees when it goes out of scope here
+    eprintln!("Finished processing suite: {:?}.", suite.path());
     Ok(())
 }
+
+/// Iterates through benchmark suites based on filter criteria and executes an action.
+///
+/// With `options.jobs > 1`, suites are processed on that many threads. Suites that
+/// share a repo and commit (and therefore a worktree) always run one after
+/// another on the same thread. Suites are dispatched in path order, but
+/// `action` may be called for different suites concurrently, so callers that
+/// collect results must order them themselves.
+///
+/// Suites without `git-info.json` or whose worktree can't be checked out are
+/// skipped and returned as failures. By default, a failing `action` stops
+/// processing and its error is returned; with `options.keep_going`, it is
+/// recorded as a failure and the remaining suites are still processed.
+///
+/// Progress is printed to stderr, so callers can print reports to stdout.
+///
+/// # Arguments
+/// * `benchmarks_root`: The root directory containing all benchmark suites.
+/// * `filters`: Criteria to select which benchmarks to process (repo names, commits).
+/// * `local_resolver`: Used to find local source code overrides.
+/// * `cache_root`: The directory used for caching Git repositories.
+/// * `options`: Worktree caching, parallelism and failure handling.
+/// * `action`: A closure to execute for each matching benchmark suite.
+///             It receives the `BenchmarkSuite` and the `GitWorktreeDir`.
+///
+/// # Returns
+/// The failures of suites that were skipped, sorted by suite path.
+pub fn process_benchmarks<F>(
+    benchmarks_root: &Path,
+    filters: &BenchmarkArgs, // Pass by reference
+    local_resolver: &LocalPathResolver, // Pass by reference
+    cache_root: &Path,
+    options: ProcessOptions,
+    action: F, // Must be Sync since it may run on several threads
+) -> Result<Vec<SuiteFailure>>
+where
+    F: Fn(&BenchmarkSuite, &GitWorktreeDir) -> Result<()> + Sync,
+{
+    let ProcessOptions { use_cache, jobs, keep_going } = options;
+    eprintln!(
+        "Searching for benchmarks in: {:?}",
+        benchmarks_root.canonicalize().unwrap_or_else(|_| benchmarks_root.to_path_buf())
+    );
+    eprintln!("Applying filters: {:?}", filters);
+    eprintln!("Worktree cache preference: {}", if use_cache { "Cached" } else { "Temporary" });
+
+    let cache_root = cache_root.canonicalize()?;
+    let cache_repo = CachedRepository::new(cache_root.to_path_buf(), local_resolver);
+
+    let suites = discover_suites(benchmarks_root, filters)?;
+
+    // Group suites by worktree so no two threads ever share one.
+    let mut groups: Vec<Vec<&BenchmarkSuite>> = Vec::new();
+    for suite in &suites {
+        match groups.iter_mut().find(|group| {
+            group[0].repo_name() == suite.repo_name() && group[0].commit_hash() == suite.commit_hash()
+        }) {
+            Some(group) => group.push(suite),
+            None => groups.push(vec![suite]),
+        }
+    }
+
+    let next_group = AtomicUsize::new(0);
+    let failures: Mutex<Vec<SuiteFailure>> = Mutex::new(Vec::new());
+    let first_error: Mutex<Option<anyhow::Error>> = Mutex::new(None);
+    let worker = || loop {
+        // Stop dispatching new suites once something failed
+        if first_error.lock().unwrap().is_some() {
+            break;
+        }
+        let group_idx = next_group.fetch_add(1, Ordering::SeqCst);
+        let Some(group) = groups.get(group_idx) else {
+            break;
+        };
+        for suite in group {
+            let Err(failure) = process_suite(&cache_repo, suite, use_cache, &action) else {
+                continue;
+            };
+            let is_action_failure =
+                !matches!(failure.stage, FailureStage::GitInfo | FailureStage::Worktree);
+            if is_action_failure && !keep_going {
+                // Propagate the first error encountered.
+                let e = failure
+                    .error
+                    .context(format!("Action failed for suite {:?}", failure.suite_path));
+                first_error.lock().unwrap().get_or_insert(e);
+                break;
+            }
+            if is_action_failure {
+                eprintln!(
+                    "Warning: Action failed for suite {:?} ({} stage): {}. Continuing.",
+                    failure.suite_path, failure.stage, failure.error
+                );
+            }
+            failures.lock().unwrap().push(failure);
+        }
+    };
+
+    let jobs = jobs.clamp(1, groups.len().max(1));
+    if jobs == 1 {
+        // Run on the current thread (the TUI relies on this)
+        worker();
+    } else {
+        eprintln!("Processing {} suites with {} jobs", suites.len(), jobs);
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(worker);
+            }
+        });
+    }
+
+    if let Some(e) = first_error.into_inner().unwrap() {
+        return Err(e);
+    }
+
+    let mut failures = failures.into_inner().unwrap();
+    failures.sort_by(|a, b| a.suite_path.cmp(&b.suite_path));
+
+    eprintln!("Finished processing all matching benchmarks.");
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern: &str) -> NamePattern {
+        NamePattern::parse(pattern).unwrap()
+    }
+
+    #[test]
+    fn globs_match_the_whole_name() {
+        assert!(pattern("flux-*").matches("flux-demo"));
+        assert!(!pattern("flux-*").matches("my-flux-demo"));
+        assert!(pattern("v?c").matches("vec"));
+        assert!(!pattern("v?c").matches("vecs"));
+        // Regex metacharacters are literal in globs
+        assert!(pattern("a.b").matches("a.b"));
+        assert!(!pattern("a.b").matches("axb"));
+    }
+
+    #[test]
+    fn regexes_match_anywhere_in_the_name() {
+        assert!(pattern("re:L1[0-9]-").matches("abc1234-foo-L12-0"));
+        assert!(!pattern("re:L1[0-9]-").matches("abc1234-foo-L22-0"));
+        assert!(pattern("re:^abc").matches("abc1234-foo-L12-0"));
+    }
+
+    #[test]
+    fn invalid_regexes_are_rejected() {
+        assert!(NamePattern::parse("re:(").is_err());
+    }
+
+    #[test]
+    fn globs_match_commits_they_are_a_prefix_of() {
+        let commit = "abc1234def5678";
+        assert!(pattern("abc1234").matches_commit(commit));
+        assert!(pattern("abc*").matches_commit(commit));
+        assert!(!pattern("abd").matches_commit(commit));
+        assert!(!pattern("abc1234").matches("abc1234def5678"));
+        // Regexes already match anywhere
+        assert!(pattern("re:def5").matches_commit(commit));
+    }
+}
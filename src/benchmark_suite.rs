@@ -1,3 +1,4 @@
+use crate::file_lock::{write_atomic, FileLock};
 use crate::types::{ErrorAndFixes, GitInformation};
 use anyhow::{anyhow, Context, Result};
 use std::{
@@ -13,6 +14,8 @@ pub struct BenchmarkSuite {
     subdir_name: String,
     commit_hash: String,
     git_info: Option<GitInformation>, // Loaded from git-info.json if it exists
+    /// Held while a session edits this suite (see `lock`)
+    lock: Option<FileLock>,
 }
 
 impl BenchmarkSuite {
@@ -66,14 +69,43 @@ impl BenchmarkSuite {
             subdir_name: subdir_name.to_string(),
             commit_hash: commit_hash.to_string(),
             git_info,
+            lock: None,
         })
     }
 
+    /// Locks the suite against writes from other sessions (processes) until
+    /// this instance is dropped. Does nothing if it is already locked.
+    pub fn lock(&mut self) -> Result<()> {
+        if self.lock.is_none() {
+            fs::create_dir_all(&self.suite_path).with_context(|| {
+                format!("Failed to create suite directory: {:?}", self.suite_path)
+            })?;
+            let what = format!("Benchmark suite {:?}", self.suite_path);
+            self.lock = Some(FileLock::acquire(&self.suite_path.join(".lock"), &what)?);
+        }
+        Ok(())
+    }
+
     /// Returns the path to this suite's directory.
     pub fn path(&self) -> &Path {
         &self.suite_path
     }
 
+    /// Returns the logical name of the repository this suite belongs to.
+    pub fn repo_name(&self) -> &str {
+        &self.repo_name
+    }
+
+    /// Returns the name of the subdirectory (of the repo) this suite belongs to.
+    pub fn subdir_name(&self) -> &str {
+        &self.subdir_name
+    }
+
+    /// Returns the full commit hash this suite was generated at.
+    pub fn commit_hash(&self) -> &str {
+        &self.commit_hash
+    }
+
     /// Returns the loaded GitInfoFile, if it was found when creating the suite.
     pub fn git_info(&self) -> Option<&GitInformation> {
         self.git_info.as_ref()
@@ -84,9 +116,18 @@ impl BenchmarkSuite {
     ///
     /// Returns an empty Vec if the directory doesn't exist or contains no benchmark files.
     pub fn load_benchmarks(&self) -> Result<Vec<ErrorAndFixes>> {
-        let mut benchmarks = Vec::new();
+        self.benchmark_files()?
+            .iter()
+            .map(|path| Self::read_benchmark_file(path))
+            .collect()
+    }
+
+    /// Returns the paths of the benchmark files (`*.json` files, excluding
+    /// `git-info.json`) in the suite's directory, sorted.
+    pub fn benchmark_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
         if !self.suite_path.exists() {
-            return Ok(benchmarks); // Not an error, just no benchmarks yet
+            return Ok(files); // Not an error, just no benchmarks yet
         }
 
         let entries = fs::read_dir(&self.suite_path).with_context(|| {
@@ -105,30 +146,19 @@ impl BenchmarkSuite {
                 && path
                     .file_name().is_some_and(|name| name != "git-info.json")
             {
-                let content = fs::read_to_string(&path)
-                    .with_context(|| format!("Failed to read benchmark file: {:?}", path))?;
-                let benchmark: ErrorAndFixes = serde_json::from_str(&content)
-                    .with_context(|| format!("Failed to parse benchmark file: {:?}", path))?;
-                benchmarks.push(benchmark);
+                files.push(path);
             }
         }
-        Ok(benchmarks)
+        files.sort();
+        Ok(files)
     }
 
-    /// Loads a single benchmark definition by its error name.
-    pub fn load_single_benchmark(&self, error_name: &str) -> Result<Option<ErrorAndFixes>> {
-        let filename = format!("{}.json", error_name);
-        let file_path = self.suite_path.join(&filename);
-
-        if !file_path.exists() {
-            return Ok(None);
-        }
-
-        let content = fs::read_to_string(&file_path)
-            .with_context(|| format!("Failed to read benchmark file: {:?}", file_path))?;
-        let benchmark: ErrorAndFixes = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse benchmark file: {:?}", file_path))?;
-        Ok(Some(benchmark))
+    /// Reads and parses a single benchmark file.
+    pub fn read_benchmark_file(path: &Path) -> Result<ErrorAndFixes> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read benchmark file: {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse benchmark file: {:?}", path))
     }
 
     /// Writes the complete set of benchmark definitions to the suite directory,
@@ -147,35 +177,35 @@ impl BenchmarkSuite {
         benchmarks: &[ErrorAndFixes],
         git_info: &GitInformation, // Explicitly name it for clarity
     ) -> Result<()> {
-        // 1. Ensure directory exists
-        fs::create_dir_all(&self.suite_path)
-            .with_context(|| format!("Failed to create suite directory: {:?}", self.suite_path))?;
+        // 1. Ensure directory exists and no other session is writing to it.
+        // A lock taken here (rather than by the caller) is released at the end.
+        let was_locked = self.lock.is_some();
+        self.lock()?;
 
         // 2. Create/Update git-info.json
         let git_info_path = self.suite_path.join("git-info.json");
         let git_info_json =
             serde_json::to_string_pretty(&git_info).context("Failed to serialize git-info.json")?;
-        fs::write(&git_info_path, git_info_json)
+        write_atomic(&git_info_path, git_info_json)
             .with_context(|| format!("Failed to write git-info.json to {:?}", git_info_path))?;
         // Update the cached version in self
         self.git_info = Some(git_info.clone());
 
-        // 3. Write benchmark files (Consider removing old ones first?)
-        // Simplest approach: just write, overwriting existing ones.
-        // More robust: List existing *.json, remove ones not in the new list, then write.
-        // Let's stick to overwriting for now.
+        // 3. Write benchmark files, overwriting existing ones. Files of other
+        // benchmarks are left alone; use `delete_benchmarks` to remove them.
         for benchmark in benchmarks {
             let filename = format!("{}.json", benchmark.error_name); // Add sanitization if needed
             let error_path = self.suite_path.join(filename);
             let error_json = serde_json::to_string_pretty(benchmark).with_context(|| {
                 format!("Failed to serialize benchmark: {}", benchmark.error_name)
             })?;
-            fs::write(&error_path, error_json)
+            write_atomic(&error_path, error_json)
                 .with_context(|| format!("Failed to write benchmark file to {:?}", error_path))?;
         }
 
-        // Optional: Clean up stale benchmark files not present in the `benchmarks` list.
-        // self.cleanup_stale_files(benchmarks)?;
+        if !was_locked {
+            self.lock = None;
+        }
 
         println!(
             "Successfully wrote benchmarks for {}/{} at {}",
@@ -184,39 +214,22 @@ impl BenchmarkSuite {
         Ok(())
     }
 
-    // Optional helper to remove .json files not in the current benchmark list
-    #[allow(dead_code)] // Remove if used
-    fn cleanup_stale_files(&self, current_benchmarks: &[ErrorAndFixes]) -> Result<()> {
-        if !self.suite_path.exists() {
-            return Ok(());
-        }
-
-        let current_files: std::collections::HashSet<String> = current_benchmarks
-            .iter()
-            .map(|b| format!("{}.json", b.error_name))
-            .collect();
-
-        let entries = fs::read_dir(&self.suite_path).with_context(|| {
-            format!(
-                "Failed to read suite directory for cleanup: {:?}",
-                self.suite_path
-            )
-        })?;
-
-        for entry_res in entries {
-            let entry = entry_res?;
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                    if filename.ends_with(".json") && filename != "git-info.json" && !current_files.contains(filename) {
-                        println!("Removing stale benchmark file: {:?}", path);
-                        fs::remove_file(&path).with_context(|| {
-                            format!("Failed to remove stale file: {:?}", path)
-                        })?;
-                    }
-                }
+    /// Removes the benchmark files of `error_names` from the suite directory.
+    /// Benchmarks without a file (e.g. ones never written) are ignored.
+    pub fn delete_benchmarks(&mut self, error_names: &[String]) -> Result<()> {
+        let was_locked = self.lock.is_some();
+        self.lock()?;
+        for error_name in error_names {
+            let path = self.suite_path.join(format!("{}.json", error_name));
+            if path.exists() {
+                println!("Removing deleted benchmark file: {:?}", path);
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove benchmark file: {:?}", path))?;
             }
         }
+        if !was_locked {
+            self.lock = None;
+        }
         Ok(())
     }
 }
@@ -1,16 +1,26 @@
 use crate::local_paths::LocalPathResolver;
 use crate::types::RemoteInfo;
 use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 use url::Url; // For sanitizing URLs for cache paths
 
+/// Per-repository lock serializing git operations (clone, fetch, worktree
+/// add/remove) that would otherwise race when suites are processed in parallel.
+type RepoLock = Arc<Mutex<()>>;
+
 /// Manages retrieval and caching of source code repositories and worktrees.
+///
+/// It can be shared across threads: git operations on the same underlying
+/// repository are serialized through a per-repository lock.
 pub struct CachedRepository<'a> {
     cache_root: PathBuf,
     local_resolver: &'a LocalPathResolver, // Borrow the resolver
+    repo_locks: Mutex<HashMap<PathBuf, RepoLock>>,
 }
 
 /// Represents a Git worktree, which can be temporary (cleaned up on drop)
@@ -25,6 +35,8 @@ pub struct GitWorktreeDir {
     /// If Some(_), this is a temporary worktree managed by TempDir.
     /// The TempDir handle ensures the directory is cleaned up when this struct is dropped.
     _temporary_marker: Option<TempDir>,
+    /// Lock of `repo_path`, held while removing a temporary worktree.
+    repo_lock: Option<RepoLock>,
 }
 
 impl GitWorktreeDir {
@@ -40,7 +52,7 @@ impl Drop for GitWorktreeDir {
         // The TempDir's own Drop impl handles deleting the directory itself.
         if let Some(temp_dir) = self._temporary_marker.take() {
             // Using take() correctly consumes the Option<TempDir>
-            println!("Cleaning up temporary worktree: {:?}", temp_dir.path());
+            eprintln!("Cleaning up temporary worktree: {:?}", temp_dir.path());
             // Attempt to remove the worktree using the git command line
             // Run the command relative to the *original* repository path
             let repo_path_str = match self.repo_path.to_str() {
@@ -51,6 +63,10 @@ impl Drop for GitWorktreeDir {
                      return;
                  }
             };
+            let _guard = self
+                .repo_lock
+                .as_ref()
+                .map(|lock| lock.lock().unwrap_or_else(|e| e.into_inner()));
             let status = Command::new("git")
                 .args(["-C", repo_path_str]) // Run in the main repo dir (bare or local)
                 .args(["worktree", "remove", "--force"]) // Force removal even if dirty
@@ -59,7 +75,7 @@ impl Drop for GitWorktreeDir {
 
             match status {
                 Ok(s) if s.success() => {
-                    println!("Successfully removed temporary worktree entry for: {:?}", temp_dir.path());
+                    eprintln!("Successfully removed temporary worktree entry for: {:?}", temp_dir.path());
                 }
                 Ok(s) => {
                      eprintln!(
@@ -77,7 +93,7 @@ impl Drop for GitWorktreeDir {
             // temp_dir is dropped here, cleaning up the actual directory on disk.
         } else {
             // This is a cached worktree, do nothing on drop.
-            println!("Skipping cleanup for cached worktree: {:?}", self.worktree_path);
+            eprintln!("Skipping cleanup for cached worktree: {:?}", self.worktree_path);
         }
     }
 }
@@ -93,9 +109,20 @@ impl<'a> CachedRepository<'a> {
         CachedRepository {
             cache_root,
             local_resolver,
+            repo_locks: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Returns the lock guarding git operations on the repository at `repo_path`.
+    fn repo_lock(&self, repo_path: &Path) -> RepoLock {
+        self.repo_locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(repo_path.to_path_buf())
+            .or_default()
+            .clone()
+    }
+
     /// Gets a path to a working directory for the specified repository and commit.
     ///
     /// Behavior depends on `use_cache`:
@@ -133,7 +160,7 @@ impl<'a> CachedRepository<'a> {
     ) -> Result<GitWorktreeDir> {
         // 1. Try resolving local path first
         if let Some(local_repo_path) = self.local_resolver.resolve(repo_name, commit_hash) {
-            println!(
+            eprintln!(
                 "Found local path override: {:?}. Cache preference: {}",
                 local_repo_path,
                 if use_cache { "Cached" } else { "Temporary" }
@@ -151,15 +178,17 @@ impl<'a> CachedRepository<'a> {
                 // This parameter is only for determining whether to _save_ to a cache.
                 //
                 // Local path exists, decide whether to cache or use temporary based on use_cache
+                let repo_lock = self.repo_lock(local_repo_path);
+                let _guard = repo_lock.lock().unwrap_or_else(|e| e.into_inner());
                 if use_cache {
                     // Attempt to create or get a *cached* worktree from the *local* repo
-                    println!("Attempting to use cached worktree for local override.");
+                    eprintln!("Attempting to use cached worktree for local override.");
                     let worktree_path = self.calculate_local_cached_worktree_path(repo_name, commit_hash)?;
                     return self.create_or_get_cached_worktree(local_repo_path, &worktree_path, commit_hash)
                         .with_context(|| format!("Failed to get/create cached worktree from local override path: {:?}", local_repo_path));
                 } else {
                     // Create a temporary worktree from the local repo (existing behavior for use_cache=false)
-                    println!("Creating temporary worktree for local override.");
+                    eprintln!("Creating temporary worktree for local override.");
                     return self.create_temporary_worktree(local_repo_path, commit_hash)
                         .with_context(|| format!("Failed to create temporary worktree from local override path: {:?}", local_repo_path));
                 }
@@ -167,7 +196,7 @@ impl<'a> CachedRepository<'a> {
         }
 
         // 2. No valid local override, proceed with cache/remote logic
-        println!(
+        eprintln!(
             "Using cache/remote for {}@{}. Cache preference: {}",
             repo_name,
             &commit_hash[..7],
@@ -181,6 +210,8 @@ impl<'a> CachedRepository<'a> {
         };
 
         let cached_repo_path = self.ensure_repo_path_exists(&remote.remote_url)?;
+        let repo_lock = self.repo_lock(&cached_repo_path);
+        let _guard = repo_lock.lock().unwrap_or_else(|e| e.into_inner());
         let repo = self.ensure_cached_repo(&cached_repo_path, &remote.remote_url)?;
         self.fetch_commit(&repo, commit_hash, remote)?;
 
@@ -217,13 +248,13 @@ impl<'a> CachedRepository<'a> {
     /// Ensures the repository exists in the cache, cloning if necessary.
     fn ensure_cached_repo(&self, path: &Path, remote_url: &str) -> Result<git2::Repository> {
         if path.join(".git").exists() || path.join("HEAD").exists() { // Check if it looks like a git repo (bare or non-bare)
-            println!("Cache hit: Found repository at {:?}", path);
+            eprintln!("Cache hit: Found repository at {:?}", path);
             let repo = git2::Repository::open(path)
                 .with_context(|| format!("Failed to open cached repository at {:?}", path))?;
             // Optional: Add verification logic here (e.g., check remote URL) if needed
             Ok(repo)
         } else {
-            println!("Cache miss: Cloning {} into {:?}", remote_url, path);
+            eprintln!("Cache miss: Cloning {} into {:?}", remote_url, path);
             // Parent directory should exist from ensure_repo_path_exists
 
             // Use git2 for cloning
@@ -254,11 +285,11 @@ impl<'a> CachedRepository<'a> {
 
         // Check if commit exists locally
         if repo.find_commit(oid).is_ok() {
-            println!("Commit {} already exists locally.", &commit_hash[..7]);
+            eprintln!("Commit {} already exists locally.", &commit_hash[..7]);
             return Ok(());
         }
 
-        println!(
+        eprintln!(
             "Commit {} not found locally, fetching from '{}' ({})",
             &commit_hash[..7], remote_info.remote_name, remote_info.remote_url
         );
@@ -283,7 +314,7 @@ impl<'a> CachedRepository<'a> {
         // Fetch default branches and the specific commit
         match remote.fetch(&[&refspec, &refspec_commit], Some(&mut fo), None) {
             Ok(_) => {
-                println!("Successfully fetched refspecs including potential commit {}.", &commit_hash[..7]);
+                eprintln!("Successfully fetched refspecs including potential commit {}.", &commit_hash[..7]);
                 // Verify commit exists now
                 if repo.find_commit(oid).is_ok() {
                     return Ok(());
@@ -336,7 +367,7 @@ impl<'a> CachedRepository<'a> {
         let temp_dir = tempfile::tempdir()?;
         let worktree_path = temp_dir.path().to_path_buf();
 
-        println!("Creating temporary worktree at: {:?}", worktree_path);
+        eprintln!("Creating temporary worktree at: {:?}", worktree_path);
 
         // Use shell out for robustness, run relative to the source repo path
         let repo_path_str = repo_path.to_str().ok_or_else(|| anyhow::anyhow!("Invalid repo path string: {:?}", repo_path))?;
@@ -361,6 +392,7 @@ impl<'a> CachedRepository<'a> {
             repo_path: repo_path.to_path_buf(), // Store the path of the repo it came from
             worktree_path,
             _temporary_marker: Some(temp_dir), // Mark as temporary
+            repo_lock: Some(self.repo_lock(repo_path)),
         })
     }
 
@@ -374,11 +406,12 @@ impl<'a> CachedRepository<'a> {
         let git_file_path = worktree_path.join(".git");
         if worktree_path.is_dir() && git_file_path.is_file() {
              // Optional: Add more validation, e.g., read .git file, run `git worktree list`
-             println!("Found existing cached remote worktree at: {:?}", worktree_path);
+             eprintln!("Found existing cached remote worktree at: {:?}", worktree_path);
              Some(GitWorktreeDir {
                  repo_path: repo_path.to_path_buf(), // Points to the cached bare repo
                  worktree_path: worktree_path.to_path_buf(),
                  _temporary_marker: None, // Mark as cached
+                 repo_lock: None,
              })
         } else {
             None
@@ -392,7 +425,7 @@ impl<'a> CachedRepository<'a> {
         worktree_path: &Path,
         commit_hash: &str,
     ) -> Result<GitWorktreeDir> {
-        if let Some(found_worktree) = self.lookup_cached_worktree(repo_path, &worktree_path) {
+        if let Some(found_worktree) = self.lookup_cached_worktree(repo_path, worktree_path) {
             return Ok(found_worktree);
         }
         let repo = git2::Repository::open(repo_path)
@@ -414,11 +447,12 @@ impl<'a> CachedRepository<'a> {
         let git_file_path = worktree_path.join(".git");
         if worktree_path.is_dir() && git_file_path.is_file() {
              // Optional: Add more validation, e.g., read .git file, run `git worktree list`
-             println!("Found existing cached remote worktree at: {:?}", worktree_path);
+             eprintln!("Found existing cached remote worktree at: {:?}", worktree_path);
              return Ok(GitWorktreeDir {
                  repo_path: repo_path.to_path_buf(), // Points to the cached bare repo
                  worktree_path: worktree_path.to_path_buf(),
                  _temporary_marker: None, // Mark as cached
+                 repo_lock: None,
              });
         }
 
@@ -427,8 +461,8 @@ impl<'a> CachedRepository<'a> {
         }
 
 
-        println!("Creating new cached remote worktree at: {:?}", worktree_path);
-        fs::create_dir_all(&worktree_path).with_context(|| format!("Failed to create directory for cached worktree: {:?}", worktree_path))?;
+        eprintln!("Creating new cached remote worktree at: {:?}", worktree_path);
+        fs::create_dir_all(worktree_path).with_context(|| format!("Failed to create directory for cached worktree: {:?}", worktree_path))?;
 
 
         // Use shell out for robustness, relative to the cached bare repo
@@ -436,14 +470,14 @@ impl<'a> CachedRepository<'a> {
         let status = Command::new("git")
             .args(["-C", repo_path_str])
             .args(["worktree", "add", "--detach"])
-            .arg(&worktree_path)
+            .arg(worktree_path)
             .arg(commit_hash)
             .status()
             .context("Failed to execute git worktree add command for cached remote worktree")?;
 
         if !status.success() {
             // Clean up the created directory if 'git worktree add' failed
-            fs::remove_dir_all(&worktree_path)?;
+            fs::remove_dir_all(worktree_path)?;
             bail!(
                 "'git worktree add' command failed for cached remote worktree (commit {}) at {:?} from repo {:?}",
                 commit_hash,
@@ -456,6 +490,7 @@ impl<'a> CachedRepository<'a> {
             repo_path: repo_path.to_path_buf(), // Points to the cached bare repo
             worktree_path: worktree_path.to_path_buf(),
             _temporary_marker: None, // Mark as cached
+            repo_lock: None,
         })
     }
 
@@ -543,10 +578,8 @@ impl<'a> CachedRepository<'a> {
             .collect::<String>();
 
         // Remove trailing .git or _git
-        if safe_path.ends_with("_git") {
+        if safe_path.ends_with("_git") || safe_path.ends_with(".git") {
             safe_path.truncate(safe_path.len() - 4);
-        } else if safe_path.ends_with(".git") {
-             safe_path.truncate(safe_path.len() - 4);
         }
 
         // Handle empty path case (e.g., URL is just hostname)
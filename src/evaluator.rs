@@ -1,64 +1,82 @@
-use crate::types::{CompilerMessage, Diagnostic, ErrorAndFixes, Fix, FixLine, LineLoc};
-use serde::Deserialize;
+use crate::types::{ColumnRange, CompilerMessage, Diagnostic, ErrorAndFixes, FixLine, LineLoc};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashSet, VecDeque};
-use std::path::{Path, PathBuf};
-use comfy_table::{Table, Row, Cell, ContentArrangement}; // Using comfy-table for easier table generation
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use comfy_table::{Table, Cell, ContentArrangement}; // Using comfy-table for easier table generation
 
 // --- Structs for Deserializing ConstraintDebugInfo ---
 // These mirror the structure of your Serialize impls, assuming simple JSON types
 // for fields with custom serializers.
 
 #[derive(Debug, Clone, Deserialize)]
-struct SimpleLocDeserialize {
-    line: usize,
-    char: usize,
-    file: String,
+pub struct SimpleLocDeserialize {
+    pub line: usize,
+    pub char: usize,
+    pub file: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct SimpleSpanDeserialize {
-    start: SimpleLocDeserialize,
-    end: SimpleLocDeserialize,
+pub struct SimpleSpanDeserialize {
+    pub start: SimpleLocDeserialize,
+    pub end: SimpleLocDeserialize,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct SimpleFnInfoDeserialize {
+pub struct SimpleFnInfoDeserialize {
+    #[allow(dead_code)] // Part of the flux output, but not used yet
     fn_name: String,
     fn_span: Option<SimpleSpanDeserialize>,
 }
 
 // Assuming BinderOriginator and Name serialize to simple strings for debug output
 #[derive(Debug, Clone, Deserialize)]
-struct BinderDebugInfoDeserialize {
-    name: String, // Read as String based on `serialize_debug`
-    pretty_name: Option<String>,
-    span: Option<SimpleSpanDeserialize>,
-    originator: Option<String>, // Read as String based on `serialize_debug`
-    depth: usize,
+pub struct BinderDebugInfoDeserialize {
+    pub name: String, // Read as String based on `serialize_debug`
+    pub pretty_name: Option<String>,
+    pub span: Option<SimpleSpanDeserialize>,
+    pub originator: Option<String>, // Read as String based on `serialize_debug`
+    pub depth: usize,
+    #[allow(dead_code)] // Part of the flux output, but not used yet
     related_vars: HashSet<String>, // Read as HashSet<String> based on `serialize_set_debug`
+    #[allow(dead_code)]
     in_constraint: bool,
     related_function: Option<SimpleFnInfoDeserialize>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct BlameSpanDebugInfoDeserialize {
-    binder_name: String, // Read as String based on `serialize_debug`
-    blame_span: Option<SimpleSpanDeserialize>,
-    suggested_refinement: Option<String>, // Keep as Option<String>
+pub struct BlameSpanDebugInfoDeserialize {
+    pub binder_name: String, // Read as String based on `serialize_debug`
+    pub blame_span: Option<SimpleSpanDeserialize>,
+    pub suggested_refinement: Option<String>, // Keep as Option<String>
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ConstraintDebugInfoDeserialize { // Made pub as it's returned
     // Assuming rty::Expr serializes to a simple string for debug output
-    constraint: String, // Read as String based on `serialize_debug`
-    binders: Vec<BinderDebugInfoDeserialize>,
-    blame_spans: Vec<BlameSpanDebugInfoDeserialize>,
+    pub constraint: String, // Read as String based on `serialize_debug`
+    pub binders: Vec<BinderDebugInfoDeserialize>,
+    /// Most likely culprit first
+    pub blame_spans: Vec<BlameSpanDebugInfoDeserialize>,
+    /// Number of debug-info notes this was combined from (not part of the flux output)
+    #[serde(skip, default = "one")]
+    num_notes: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+impl ConstraintDebugInfoDeserialize {
+    pub fn num_notes(&self) -> usize {
+        self.num_notes
+    }
 }
 
 // --- Structs for Evaluation Results ---
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FixEvalResult {
     pub num_correct_lines: usize,
     pub num_correct_lines_all_binders: usize,
@@ -66,6 +84,42 @@ pub struct FixEvalResult {
     pub missing_lines_all_binders: Vec<LineLoc>, // Contains the expected lines not found in blame spans
     pub num_total_lines: usize,
     pub is_trivial: Option<bool>,
+    /// Number of blame spans that contain at least one of this fix's lines
+    #[serde(default)]
+    pub num_blame_spans_hit: usize,
+    /// Number of blame spans that contain none of this fix's lines
+    #[serde(default)]
+    pub num_spurious_blame_spans: usize,
+    /// Number of distinct source lines covered by any blame span
+    #[serde(default)]
+    pub num_blamed_lines: usize,
+    /// For each fix line (in order), the 1-based rank of the first blame span
+    /// containing it, or `None` if no blame span does
+    #[serde(default)]
+    pub line_ranks: Vec<Option<usize>>,
+    /// One entry per fix line with an annotated refinement (`added_reft`)
+    #[serde(default)]
+    pub refinement_evals: Vec<RefinementEvalResult>,
+    /// For each fix line (in order), the size in lines of the smallest blame
+    /// span containing it, or `None` if no blame span does
+    #[serde(default)]
+    pub hit_span_sizes: Vec<Option<usize>>,
+}
+
+/// How flux's suggested refinement at a fix line compares to the refinement
+/// the annotator added there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefinementEvalResult {
+    pub line: LineLoc,
+    pub expected: String,
+    /// The suggestion of the highest ranked blame span containing the line
+    /// that has one. `None` if no such blame span exists.
+    pub suggested: Option<String>,
+    pub is_exact_match: bool,
+    /// Equal after removing whitespace and redundant enclosing parentheses
+    pub is_normalized_match: bool,
+    /// Token-level similarity in [0, 1] (see `token_similarity`)
+    pub similarity: f64,
 }
 
 impl FixEvalResult {
@@ -116,14 +170,103 @@ impl FixEvalResult {
     pub fn is_fully_incorrect_all_binders(&self) -> bool {
         self.num_total_lines > 0 && self.num_correct_lines_all_binders == 0
     }
+
+    // Fraction of (located) blame spans that hit a fix line. 0 if nothing was blamed.
+    pub fn precision(&self) -> f64 {
+        let num_blame_spans = self.num_blame_spans_hit + self.num_spurious_blame_spans;
+        if num_blame_spans == 0 {
+            0.0
+        } else {
+            self.num_blame_spans_hit as f64 / num_blame_spans as f64
+        }
+    }
+
+    // How many lines the blame spans cover per expected fix line. 1.0 is a
+    // perfectly focused diagnosis; larger values mean more code to inspect.
+    pub fn line_coverage(&self) -> f64 {
+        if self.num_total_lines == 0 {
+            0.0
+        } else {
+            self.num_blamed_lines as f64 / self.num_total_lines as f64
+        }
+    }
+
+    // Rank of the first blame span that hits any of this fix's lines, i.e. how
+    // far down the list a user has to read before being pointed at this fix.
+    pub fn first_hit_rank(&self) -> Option<usize> {
+        self.line_ranks.iter().flatten().min().copied()
+    }
+
+    // Mean over the fix lines of 1/rank (0 for lines no blame span contains)
+    pub fn mean_reciprocal_rank(&self) -> f64 {
+        if self.line_ranks.is_empty() {
+            0.0
+        } else {
+            self.line_ranks
+                .iter()
+                .map(|rank| rank.map_or(0.0, |rank| 1.0 / rank as f64))
+                .sum::<f64>()
+                / self.line_ranks.len() as f64
+        }
+    }
+
+    // Like `ratio`, but each found line only counts 1/n where n is the number
+    // of lines of the smallest blame span containing it. A span covering a
+    // whole 40-line function barely counts; a single-line span counts fully.
+    pub fn size_weighted_ratio(&self) -> f64 {
+        if self.hit_span_sizes.is_empty() {
+            0.0
+        } else {
+            self.hit_span_sizes
+                .iter()
+                .map(|size| size.map_or(0.0, |size| 1.0 / size.max(1) as f64))
+                .sum::<f64>()
+                / self.hit_span_sizes.len() as f64
+        }
+    }
+
+    pub fn num_refinements_exact(&self) -> usize {
+        self.refinement_evals.iter().filter(|eval| eval.is_exact_match).count()
+    }
+
+    pub fn num_refinements_normalized(&self) -> usize {
+        self.refinement_evals.iter().filter(|eval| eval.is_normalized_match).count()
+    }
+
+    // Mean token similarity over the annotated refinements. `None` if the fix
+    // has no annotated refinements.
+    pub fn mean_refinement_similarity(&self) -> Option<f64> {
+        if self.refinement_evals.is_empty() {
+            None
+        } else {
+            Some(
+                self.refinement_evals.iter().map(|eval| eval.similarity).sum::<f64>()
+                    / self.refinement_evals.len() as f64,
+            )
+        }
+    }
+
+    // Harmonic mean of `precision` and `ratio` (recall)
+    pub fn f1(&self) -> f64 {
+        let precision = self.precision();
+        let recall = self.ratio();
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorEvalResult {
     pub error_name: String,
     pub fix_evals: Vec<FixEvalResult>, // One result per Fix in ErrorAndFixes
     pub num_blamed: usize,
     pub num_binders: usize,
+    /// Number of constraint debug-info notes flux emitted for the error
+    #[serde(default = "one")]
+    pub num_debug_notes: usize,
 }
 
 impl ErrorEvalResult {
@@ -142,6 +285,22 @@ impl ErrorEvalResult {
             .fold(0.0, f64::max) // Find the maximum ratio
     }
 
+    // Finds the best (lowest) first-hit rank among all fixes for this error
+    pub fn best_first_hit_rank(&self) -> Option<usize> {
+        self.fix_evals
+            .iter()
+            .filter_map(|eval| eval.first_hit_rank())
+            .min()
+    }
+
+    // Finds the best mean reciprocal rank among all fixes for this error
+    pub fn best_mean_reciprocal_rank(&self) -> f64 {
+        self.fix_evals
+            .iter()
+            .map(|eval| eval.mean_reciprocal_rank())
+            .fold(0.0, f64::max)
+    }
+
     // Checks if *any* fix was fully correct (standard)
     pub fn is_any_fix_fully_correct(&self) -> bool {
        self.fix_evals.iter().any(|eval| eval.is_fully_correct())
@@ -165,82 +324,499 @@ impl ErrorEvalResult {
    }
 }
 
+/// An `ErrorEvalResult` tagged with the suite it was evaluated in, used for
+/// the machine-readable (JSON/CSV) eval output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalRecord {
+    pub repo_name: String,
+    pub subdir: PathBuf,
+    pub commit: String,
+    /// Fingerprint of the benchmark (see `ErrorAndFixes::fingerprint`), so
+    /// runs can be compared even if the error was renamed. Missing in older
+    /// reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(flatten)]
+    pub result: ErrorEvalResult,
+}
+
+/// Why an error appears in only one of the benchmarks and the flux output,
+/// and so couldn't be evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmatchedReason {
+    /// A benchmark whose error flux didn't emit (fixed, or renamed)
+    NotEmitted,
+    /// A benchmark whose error flux emitted without constraint debug info
+    NoDebugInfo,
+    /// A benchmark whose error flux emitted with debug info that couldn't be
+    /// parsed
+    DebugInfoParseError,
+    /// An error flux emitted that has no benchmark
+    NoBenchmark,
+}
+
+impl UnmatchedReason {
+    /// The (serialized) identifier of the reason, as used in the CSV report
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnmatchedReason::NotEmitted => "not_emitted",
+            UnmatchedReason::NoDebugInfo => "no_debug_info",
+            UnmatchedReason::DebugInfoParseError => "debug_info_parse_error",
+            UnmatchedReason::NoBenchmark => "no_benchmark",
+        }
+    }
+}
+
+impl fmt::Display for UnmatchedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnmatchedReason::NotEmitted => write!(f, "Benchmarks Not Emitted By Flux"),
+            UnmatchedReason::NoDebugInfo => write!(f, "Benchmarks Emitted Without Debug Info"),
+            UnmatchedReason::DebugInfoParseError => write!(f, "Benchmarks Emitted With Unparsable Debug Info"),
+            UnmatchedReason::NoBenchmark => write!(f, "Flux Errors Without Benchmark"),
+        }
+    }
+}
+
+/// An error that couldn't be evaluated, tagged with its suite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnmatchedError {
+    pub repo_name: String,
+    pub subdir: PathBuf,
+    pub commit: String,
+    pub error_name: String,
+    pub reason: UnmatchedReason,
+}
+
 // --- Function Implementations ---
 
 const DEBUG_INFO_PREFIX: &str = "constraint_debug_info: ";
 
 /// Searches for the serialized ConstraintDebugInfo within a CompilerMessage's child notes.
 ///
-/// It checks the `message` field of direct children Diagnostics with level "note".
+/// It checks the `message` field of all (arbitrarily nested) children Diagnostics
+/// with level "note". A single error may carry several notes (e.g. when several
+/// constraints fail behind one error), in which case they are combined into one
+/// `ConstraintDebugInfoDeserialize` (see `combine_constraint_debug_info`) whose
+/// `num_notes` says how many were found.
 ///
 /// # Arguments
 /// * `message`: The top-level `CompilerMessage` potentially containing the info.
 ///
 /// # Returns
-/// * `Ok(Some(ConstraintDebugInfoDeserialize))` if found and parsed successfully.
+/// * `Ok(Some(ConstraintDebugInfoDeserialize))` if at least one note was found and
+///   all found notes parsed successfully.
 /// * `Ok(None)` if no matching note is found.
-/// * `Err(anyhow::Error)` if JSON parsing fails.
+/// * `Err(anyhow::Error)` if JSON parsing of any note fails.
 pub fn extract_constraint_debug_info(
     message: &CompilerMessage,
 ) -> anyhow::Result<Option<ConstraintDebugInfoDeserialize>> {
-    for child_diagnostic in &message.message.children {
+    let mut infos = Vec::new();
+    collect_constraint_debug_info(&message.message.children, &mut infos)?;
+    Ok(combine_constraint_debug_info(infos))
+}
+
+/// Recursively parses every debug-info note in `diagnostics` (and their
+/// children) into `infos`, in depth-first order.
+fn collect_constraint_debug_info(
+    diagnostics: &[Diagnostic],
+    infos: &mut Vec<ConstraintDebugInfoDeserialize>,
+) -> anyhow::Result<()> {
+    for child_diagnostic in diagnostics {
         if child_diagnostic.level == "note" {
             if let Some(escaped_json_str) = child_diagnostic.message.strip_prefix(DEBUG_INFO_PREFIX) {
                 // let unescaped_json_str = unescaper::unescape(escaped_json_str)?;
-                match serde_json::from_str::<ConstraintDebugInfoDeserialize>(&escaped_json_str) {
-                    Ok(parsed_info) => {
-                        // Successfully parsed
-                        return Ok(Some(parsed_info));
-                    }
-                    Err(e) => {
-                        // Parsing failed, return the error
-                        return Err(anyhow::anyhow!(
+                let parsed_info = serde_json::from_str::<ConstraintDebugInfoDeserialize>(escaped_json_str)
+                    .map_err(|e| {
+                        anyhow::anyhow!(
                             "Failed to parse ConstraintDebugInfo JSON: {}. JSON string: '{}'",
                             e, escaped_json_str
-                        ));
-                    }
-                }
+                        )
+                    })?;
+                infos.push(parsed_info);
             }
         }
-        // Potential improvement: Recursively search deeper children?
-        // For now, sticking to direct children as per the prompt's implication.
+        collect_constraint_debug_info(&child_diagnostic.children, infos)?;
+    }
+    Ok(())
+}
+
+/// Removes the debug-info notes from `message` (its children, recursively,
+/// and its rendered text), e.g. so they aren't saved with a benchmark.
+pub fn strip_constraint_debug_info(message: &mut CompilerMessage) {
+    fn strip_children(diagnostics: &mut Vec<Diagnostic>) {
+        diagnostics.retain(|child| {
+            !(child.level == "note" && child.message.starts_with(DEBUG_INFO_PREFIX))
+        });
+        for child in diagnostics {
+            strip_children(&mut child.children);
+        }
+    }
+    strip_children(&mut message.message.children);
+    if let Some(rendered) = &mut message.message.rendered {
+        *rendered = rendered
+            .lines()
+            .filter(|line| !line.contains(DEBUG_INFO_PREFIX))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+}
+
+/// Combines the debug info of several notes into one.
+///
+/// The blame spans are interleaved by rank (the top span of every note, then
+/// the second of every note, ...) so that ranking stays meaningful across
+/// notes. Binders are concatenated and the constraints joined with `&&`.
+///
+/// # Returns
+/// * `None` if `infos` is empty.
+fn combine_constraint_debug_info(
+    infos: Vec<ConstraintDebugInfoDeserialize>,
+) -> Option<ConstraintDebugInfoDeserialize> {
+    if infos.len() <= 1 {
+        return infos.into_iter().next();
     }
 
-    // No matching note found among direct children
-    Ok(None)
+    let num_notes = infos.iter().map(|info| info.num_notes).sum();
+    let constraint = infos
+        .iter()
+        .map(|info| format!("({})", info.constraint))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    let max_blame_spans = infos.iter().map(|info| info.blame_spans.len()).max().unwrap_or(0);
+    let mut blame_spans = Vec::new();
+    for rank in 0..max_blame_spans {
+        blame_spans.extend(infos.iter().filter_map(|info| info.blame_spans.get(rank)).cloned());
+    }
+    let binders = infos.into_iter().flat_map(|info| info.binders).collect();
+
+    Some(ConstraintDebugInfoDeserialize {
+        constraint,
+        binders,
+        blame_spans,
+        num_notes,
+    })
 }
 
-fn in_fix_line(blame_span: &SimpleSpanDeserialize, fix_line: &FixLine) -> bool {
-    // FixLines are relative, just like the blame span paths.
-    let fix_line_path_str = fix_line.file.to_string_lossy().to_string();
-    // Compare file paths (string comparison - may be fragile)
-    blame_span.start.file == fix_line_path_str
+/// How precisely a span has to match a `FixLine` to count as a hit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpanMatching {
+    /// The fix line only has to be within the span's line range
+    #[default]
+    Lines,
+    /// For fix lines with a column range, the span has to overlap those
+    /// columns on the fix line. Fix lines without one fall back to `Lines`.
+    Columns,
+}
+
+fn in_fix_line(blame_span: &SimpleSpanDeserialize, fix_line: &FixLine, matching: SpanMatching) -> bool {
+    // FixLines are relative to the run dir, and so are the blame span paths
+    // once they went through `SpanPathResolver::resolve_spans`.
+    let in_line_range = Path::new(&blame_span.start.file) == normalize_lexically(&fix_line.file)
     // Is the line contained in the blame span?
         && fix_line.line >= blame_span.start.line
-        && fix_line.line <= blame_span.end.line
+        && fix_line.line <= blame_span.end.line;
+
+    match (matching, fix_line.columns) {
+        (SpanMatching::Columns, Some(columns)) => {
+            in_line_range && overlaps_columns(blame_span, fix_line.line, columns)
+        }
+        _ => in_line_range,
+    }
+}
+
+/// Whether the part of `span` on `line` overlaps `columns`. Assumes the span
+/// covers `line`. Span columns (`char`) are treated as 1-indexed and inclusive.
+fn overlaps_columns(span: &SimpleSpanDeserialize, line: usize, columns: ColumnRange) -> bool {
+    // A multi-line span covers its inner lines entirely, and its first and
+    // last lines from/up to the respective column.
+    let span_start = if line == span.start.line { span.start.char } else { 1 };
+    let span_end = if line == span.end.line { span.end.char } else { usize::MAX };
+    span_start <= columns.end && columns.start <= span_end
+}
+
+/// Number of lines a span covers.
+fn span_num_lines(span: &SimpleSpanDeserialize) -> usize {
+    span.end.line.saturating_sub(span.start.line) + 1
+}
+
+// --- Path Resolution ---
+
+/// Removes `.` components and resolves `..` components without touching the
+/// file system, e.g. `./src/../src/lib.rs` becomes `src/lib.rs`.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                // Keep leading `..`s of relative paths; they can't be resolved.
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Maps the file paths in flux's debug info to paths relative to the
+/// directory flux ran in (the `subdir` of the worktree), which is what
+/// `FixLine.file` is relative to.
+///
+/// Flux may report files absolute, with `./` prefixes, relative to the run
+/// dir or relative to the workspace (worktree) root.
+pub struct SpanPathResolver {
+    worktree_root: PathBuf,
+    subdir: PathBuf,
+}
+
+impl SpanPathResolver {
+    /// # Arguments
+    /// * `worktree_root`: The root of the worktree flux ran in.
+    /// * `subdir`: The directory flux ran in, relative to `worktree_root`
+    ///             (`GitInformation.subdir`).
+    pub fn new(worktree_root: &Path, subdir: &Path) -> Self {
+        // Canonicalize so absolute span paths match even when the worktree is
+        // reached through a symlink.
+        let worktree_root = worktree_root
+            .canonicalize()
+            .unwrap_or_else(|_| normalize_lexically(worktree_root));
+        Self {
+            worktree_root,
+            subdir: normalize_lexically(subdir),
+        }
+    }
+
+    fn run_dir(&self) -> PathBuf {
+        self.worktree_root.join(&self.subdir)
+    }
+
+    /// Resolves `file` to a path relative to the run dir. Returns `None` if
+    /// the file is not inside the worktree.
+    pub fn resolve(&self, file: &str) -> Option<PathBuf> {
+        let path = normalize_lexically(Path::new(file));
+        if path.is_absolute() {
+            let absolute = path.canonicalize().unwrap_or(path);
+            let relative_to_root = absolute.strip_prefix(&self.worktree_root).ok()?;
+            return pathdiff::diff_paths(relative_to_root, &self.subdir)
+                .map(|relative| normalize_lexically(&relative));
+        }
+
+        // Relative paths are usually relative to the run dir, but may also be
+        // relative to the workspace root (e.g. in a cargo workspace).
+        if self.run_dir().join(&path).exists() {
+            Some(path)
+        } else if self.worktree_root.join(&path).exists() {
+            pathdiff::diff_paths(&path, &self.subdir).map(|relative| normalize_lexically(&relative))
+        } else {
+            None
+        }
+    }
+
+    /// Rewrites every span in `constraint_info` (blame spans and binders) to
+    /// use run-dir-relative paths. Spans that can't be mapped are left as is
+    /// (and so won't match any fix line).
+    ///
+    /// # Returns
+    /// * The (deduplicated) files of blame spans that could not be mapped into
+    ///   the worktree.
+    pub fn resolve_spans(&self, constraint_info: &mut ConstraintDebugInfoDeserialize) -> Vec<String> {
+        let mut unmapped_blame_files = Vec::new();
+        for blame_span_info in &mut constraint_info.blame_spans {
+            if let Some(span) = &mut blame_span_info.blame_span {
+                if !self.resolve_span(span) && !unmapped_blame_files.contains(&span.start.file) {
+                    unmapped_blame_files.push(span.start.file.clone());
+                }
+            }
+        }
+        for binder_info in &mut constraint_info.binders {
+            if let Some(span) = &mut binder_info.span {
+                self.resolve_span(span);
+            }
+            if let Some(SimpleFnInfoDeserialize { fn_span: Some(span), .. }) = &mut binder_info.related_function {
+                self.resolve_span(span);
+            }
+        }
+        unmapped_blame_files
+    }
+
+    /// Resolves both ends of `span` in place. Returns whether its start file
+    /// could be mapped.
+    fn resolve_span(&self, span: &mut SimpleSpanDeserialize) -> bool {
+        if let Some(resolved_end) = self.resolve(&span.end.file) {
+            span.end.file = resolved_end.to_string_lossy().to_string();
+        }
+        match self.resolve(&span.start.file) {
+            Some(resolved_start) => {
+                span.start.file = resolved_start.to_string_lossy().to_string();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// --- Refinement Comparison ---
+
+/// Compares the refinement annotated at `fix_line` against the suggestion of
+/// the highest ranked blame span (that has a suggestion) containing the line.
+fn evaluate_refinement(
+    constraint_info: &ConstraintDebugInfoDeserialize,
+    fix_line: &FixLine,
+    expected: &str,
+    matching: SpanMatching,
+) -> RefinementEvalResult {
+    let suggested = constraint_info
+        .blame_spans
+        .iter()
+        .filter(|blame_span_info| {
+            blame_span_info
+                .blame_span
+                .as_ref()
+                .is_some_and(|blame_span| in_fix_line(blame_span, fix_line, matching))
+        })
+        .find_map(|blame_span_info| blame_span_info.suggested_refinement.clone());
+
+    let (is_exact_match, is_normalized_match, similarity) = match &suggested {
+        Some(suggested) => (
+            suggested.trim() == expected.trim(),
+            normalize_refinement(suggested) == normalize_refinement(expected),
+            token_similarity(suggested, expected),
+        ),
+        None => (false, false, 0.0),
+    };
+
+    RefinementEvalResult {
+        line: LineLoc::new(fix_line.line, fix_line.file.clone()),
+        expected: expected.to_string(),
+        suggested,
+        is_exact_match,
+        is_normalized_match,
+        similarity,
+    }
+}
+
+/// Removes all whitespace and any parentheses enclosing the whole refinement,
+/// so e.g. `( a+1 > 0 )` and `a + 1 > 0` normalize to the same string.
+fn normalize_refinement(refinement: &str) -> String {
+    let mut normalized: String = refinement.chars().filter(|c| !c.is_whitespace()).collect();
+    while let Some(inner) = normalized
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        // Only strip if the outer parentheses match each other: `(a)+(b)`
+        // must stay as is.
+        if !is_balanced(inner) {
+            break;
+        }
+        normalized = inner.to_string();
+    }
+    normalized
+}
+
+/// Whether the parentheses in `s` are balanced (never closing more than opened).
+fn is_balanced(s: &str) -> bool {
+    let mut depth: usize = 0;
+    for c in s.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => match depth.checked_sub(1) {
+                Some(new_depth) => depth = new_depth,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+/// Splits a refinement into identifier/number tokens and punctuation tokens,
+/// where runs of operator characters (e.g. `>=`, `&&`) form a single token.
+/// Parentheses are dropped since they rarely change the meaning of a suggestion.
+fn tokenize_refinement(refinement: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut current_is_word = false;
+    for c in refinement.chars() {
+        let is_word = c.is_alphanumeric() || c == '_' || c == '.';
+        let is_separator = c.is_whitespace() || c == '(' || c == ')';
+        if !current.is_empty() && (is_separator || is_word != current_is_word) {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if !is_separator {
+            current.push(c);
+            current_is_word = is_word;
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Token-level similarity of two refinements: `2 * LCS / (len(a) + len(b))`
+/// over their token sequences, so 1.0 means the same tokens in the same order
+/// and 0.0 means nothing in common.
+fn token_similarity(a: &str, b: &str) -> f64 {
+    let a_tokens = tokenize_refinement(a);
+    let b_tokens = tokenize_refinement(b);
+    let total = a_tokens.len() + b_tokens.len();
+    if total == 0 {
+        return 1.0;
+    }
+    // Classic LCS dynamic program, keeping only the previous row
+    let mut prev_row = vec![0usize; b_tokens.len() + 1];
+    for a_token in &a_tokens {
+        let mut row = vec![0usize; b_tokens.len() + 1];
+        for (j, b_token) in b_tokens.iter().enumerate() {
+            row[j + 1] = if a_token == b_token {
+                prev_row[j] + 1
+            } else {
+                row[j].max(prev_row[j + 1])
+            };
+        }
+        prev_row = row;
+    }
+    2.0 * prev_row[b_tokens.len()] as f64 / total as f64
 }
 
 /// Evaluates each `Fix` in `error_and_fixes` against the `blame_spans` in `constraint_info`.
 ///
 /// It checks if the line number of each `FixLine` is contained within any `BlameSpan`'s
-/// line range, comparing file paths as strings for now.
+/// line range (and, with `SpanMatching::Columns`, overlaps its column range).
+/// Span paths are expected to be relative to the run dir already (see
+/// `SpanPathResolver::resolve_spans`).
 ///
 /// # Arguments
 /// * `constraint_info`: The parsed debug information containing blame spans.
 /// * `error_and_fixes`: The benchmark data containing expected fixes.
-/// * `base_path_for_fixline`: The path relative to which `FixLine.file` should be interpreted.
-///                            This is needed to potentially compare against the absolute paths
-///                            often found in diagnostic/debug info. If `None`, direct string comparison is used.
-///                            *Warning:* Path comparison is complex. This is a basic attempt.
+/// * `matching`: How precisely spans have to match the fix lines.
 ///
 /// # Returns
 /// * `ErrorEvalResult` summarizing the evaluation for all fixes.
 pub fn evaluate_error(
     constraint_info: &ConstraintDebugInfoDeserialize,
     error_and_fixes: &ErrorAndFixes,
+    matching: SpanMatching,
 ) -> ErrorEvalResult {
     let mut fix_evals = Vec::with_capacity(error_and_fixes.fixes.len());
 
+    // Blame spans without a location can't hit anything, so they are ignored
+    // for the precision metrics.
+    let located_blame_spans: Vec<&SimpleSpanDeserialize> = constraint_info
+        .blame_spans
+        .iter()
+        .filter_map(|blame_span_info| blame_span_info.blame_span.as_ref())
+        .collect();
+    let num_blamed_lines = located_blame_spans
+        .iter()
+        .flat_map(|span| (span.start.line..=span.end.line).map(|line| (&span.start.file, line)))
+        .collect::<HashSet<_>>()
+        .len();
+
     for fix in &error_and_fixes.fixes {
         let num_total_lines = fix.fix_lines.len();
         let mut num_correct_lines = 0;
@@ -248,23 +824,43 @@ pub fn evaluate_error(
         let mut num_correct_lines_all_binders = 0;
         let mut missing_lines_all_binders = Vec::new();
 
+        let mut line_ranks = Vec::with_capacity(num_total_lines);
+        let mut refinement_evals = Vec::new();
+        let mut hit_span_sizes = Vec::with_capacity(num_total_lines);
+
         for fix_line in &fix.fix_lines {
-            let match_in_blame_spans = constraint_info.blame_spans.iter().any(|blame_span_info| {
+            // Blame spans are ordered by flux from most to least likely, so
+            // the (1-based) position of the first match is the line's rank.
+            let rank = constraint_info.blame_spans.iter().position(|blame_span_info| {
                 if let Some(blame_span) = &blame_span_info.blame_span {
-                    in_fix_line(&blame_span, fix_line)
+                    in_fix_line(blame_span, fix_line, matching)
                 } else {
                     false
                 }
-            });
+            }).map(|idx| idx + 1);
+            line_ranks.push(rank);
+            let match_in_blame_spans = rank.is_some();
+
+            hit_span_sizes.push(
+                located_blame_spans
+                    .iter()
+                    .filter(|span| in_fix_line(span, fix_line, matching))
+                    .map(|span| span_num_lines(span))
+                    .min(),
+            );
+
+            if let Some(expected) = &fix_line.added_reft {
+                refinement_evals.push(evaluate_refinement(constraint_info, fix_line, expected, matching));
+            }
 
             let match_in_all_binders = constraint_info.binders.iter().any(|binder_info| {
                 if let Some(span) = &binder_info.span {
-                    if in_fix_line(span, fix_line) {
+                    if in_fix_line(span, fix_line, matching) {
                         return true;
                     }
                 }
                 if let Some(SimpleFnInfoDeserialize {fn_span: Some(span), ..}) = &binder_info.related_function {
-                    if in_fix_line(span, fix_line) {
+                    if in_fix_line(span, fix_line, matching) {
                         return true;
                     }
                 }
@@ -285,6 +881,11 @@ pub fn evaluate_error(
             }
         }
 
+        let num_blame_spans_hit = located_blame_spans
+            .iter()
+            .filter(|span| fix.fix_lines.iter().any(|fix_line| in_fix_line(span, fix_line, matching)))
+            .count();
+
         fix_evals.push(FixEvalResult {
             num_correct_lines,
             num_correct_lines_all_binders,
@@ -292,6 +893,12 @@ pub fn evaluate_error(
             missing_lines_all_binders,
             num_total_lines,
             is_trivial: fix.is_trivial,
+            num_blame_spans_hit,
+            num_spurious_blame_spans: located_blame_spans.len() - num_blame_spans_hit,
+            num_blamed_lines,
+            line_ranks,
+            refinement_evals,
+            hit_span_sizes,
         });
     }
 
@@ -299,6 +906,7 @@ pub fn evaluate_error(
         error_name: error_and_fixes.error_name.clone(),
         num_blamed: constraint_info.blame_spans.len(),
         num_binders: constraint_info.binders.len(),
+        num_debug_notes: constraint_info.num_notes,
         fix_evals,
     }
 }
@@ -332,10 +940,12 @@ fn format_bool_pair(val1: bool, val2: bool) -> String {
 ///
 /// # Arguments
 /// * `results`: A slice containing the evaluation results for multiple errors.
+/// * `top_k`: The `k` to report a top-k blame span hit rate for, in addition
+///            to top-1 and top-3.
 ///
 /// # Returns
 /// * A `String` containing the formatted summary table.
-pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
+pub fn generate_summary_table(results: &[ErrorEvalResult], top_k: usize) -> String {
     if results.is_empty() {
         return "No evaluation results to summarize.".to_string();
     }
@@ -353,6 +963,12 @@ pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
             "All Correct?\n(Blamed/All)",
             "All Bad?\n(Std/All)",
             "Trivial?",
+            "Precision\n(Blamed)",
+            "Line\nCoverage",
+            "F1",
+            "Size-Weighted\nRatio",
+            "First Hit\nRank",
+            "MRR",
         ]);
 
     // --- Tallies ---
@@ -361,6 +977,15 @@ pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
     let mut tally_all_bad_std = 0;
     let mut tally_all_bad_all = 0;
     let mut tally_trivial_yes = 0;
+    let mut sum_precision = 0.0;
+    let mut sum_f1 = 0.0;
+    let mut sum_size_weighted = 0.0;
+    let mut num_scored = 0;
+    let mut tally_top_1 = 0;
+    let mut tally_top_3 = 0;
+    let mut tally_top_k = 0;
+    let mut sum_mrr = 0.0;
+    let mut tally_multiple_notes = 0;
     // ---
 
     for result in results {
@@ -403,8 +1028,8 @@ pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
         };
 
         // Best Correct? (Std/All)
-        let best_correct_std = best_eval_std_opt.map_or(false, |eval| eval.is_fully_correct());
-        let best_correct_all = best_eval_all_opt.map_or(false, |eval| eval.is_fully_correct_all_binders());
+        let best_correct_std = best_eval_std_opt.is_some_and(|eval| eval.is_fully_correct());
+        let best_correct_all = best_eval_all_opt.is_some_and(|eval| eval.is_fully_correct_all_binders());
         let best_correct_pair_str = format_bool_pair(best_correct_std, best_correct_all);
 
         let num_blamed_str = result.num_blamed.to_string();
@@ -416,6 +1041,37 @@ pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
         let all_bad_all = result.is_every_fix_fully_incorrect_all_binders();
         let all_bad_pair_str = format_bool_pair(all_bad_std, all_bad_all);
 
+        // Precision, Line Coverage & F1 (of the best fix)
+        let (precision_str, coverage_str, f1_str, size_weighted_str) = match best_eval_std_opt {
+            Some(best_eval) => {
+                sum_precision += best_eval.precision();
+                sum_f1 += best_eval.f1();
+                sum_size_weighted += best_eval.size_weighted_ratio();
+                num_scored += 1;
+                (
+                    format!(
+                        "{}/{}",
+                        best_eval.num_blame_spans_hit,
+                        best_eval.num_blame_spans_hit + best_eval.num_spurious_blame_spans
+                    ),
+                    if best_eval.num_total_lines == 0 {
+                        "N/A (0)".to_string()
+                    } else {
+                        format!("{:.1}x", best_eval.line_coverage())
+                    },
+                    format!("{:.2}", best_eval.f1()),
+                    format!("{:.2}", best_eval.size_weighted_ratio()),
+                )
+            }
+            None => ("N/A".to_string(), "N/A".to_string(), "N/A".to_string(), "N/A".to_string()),
+        };
+
+        // First Hit Rank & MRR (best over all fixes)
+        let best_rank = result.best_first_hit_rank();
+        let rank_str = best_rank.map_or("-".to_string(), |rank| rank.to_string());
+        let mrr = result.best_mean_reciprocal_rank();
+        let mrr_str = format!("{:.2}", mrr);
+
         // --- Add Row ---
         table.add_row(vec![
             Cell::new(&result.error_name),
@@ -426,6 +1082,12 @@ pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
             Cell::new(&best_correct_pair_str).set_alignment(comfy_table::CellAlignment::Center),
             Cell::new(&all_bad_pair_str).set_alignment(comfy_table::CellAlignment::Center),
             Cell::new(trivial_marker).set_alignment(comfy_table::CellAlignment::Center),
+            Cell::new(&precision_str).set_alignment(comfy_table::CellAlignment::Right),
+            Cell::new(&coverage_str).set_alignment(comfy_table::CellAlignment::Right),
+            Cell::new(&f1_str).set_alignment(comfy_table::CellAlignment::Right),
+            Cell::new(&size_weighted_str).set_alignment(comfy_table::CellAlignment::Right),
+            Cell::new(&rank_str).set_alignment(comfy_table::CellAlignment::Right),
+            Cell::new(&mrr_str).set_alignment(comfy_table::CellAlignment::Right),
         ]);
 
         // --- Update Tallies ---
@@ -434,6 +1096,11 @@ pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
         if all_bad_std { tally_all_bad_std += 1; }
         if all_bad_all { tally_all_bad_all += 1; }
         if trivial_is_yes { tally_trivial_yes += 1; }
+        if best_rank.is_some_and(|rank| rank <= 1) { tally_top_1 += 1; }
+        if best_rank.is_some_and(|rank| rank <= 3) { tally_top_3 += 1; }
+        if best_rank.is_some_and(|rank| rank <= top_k) { tally_top_k += 1; }
+        sum_mrr += mrr;
+        if result.num_debug_notes > 1 { tally_multiple_notes += 1; }
         // ---
     }
 
@@ -448,20 +1115,574 @@ pub fn generate_summary_table(results: &[ErrorEvalResult]) -> String {
         }
     };
 
+    let mean = |sum: f64| {
+        if num_scored == 0 {
+            0.0
+        } else {
+            sum / num_scored as f64
+        }
+    };
+
     let summary = format!(
         "\n--- Summary ({} Total Errors) ---\n\
         ✅ Best Fix Correct (Std): {} ({:.1}%)\n\
         ✅ Best Fix Correct (All): {} ({:.1}%)\n\
         ✅ All Fixes Bad (Std):    {} ({:.1}%)\n\
         ✅ All Fixes Bad (All):    {} ({:.1}%)\n\
-        ✅ Trivial Fix (Yes):      {} ({:.1}%)",
+        ✅ Trivial Fix (Yes):      {} ({:.1}%)\n\
+        ✅ Mean Precision (Std):   {:.2}\n\
+        ✅ Mean F1 (Std):          {:.2}\n\
+        ✅ Mean Size-Weighted:     {:.2}\n\
+        ✅ Top-1 Blame Hit:        {} ({:.1}%)\n\
+        ✅ Top-3 Blame Hit:        {} ({:.1}%)\n\
+        ✅ {:<22}  {} ({:.1}%)\n\
+        ✅ Mean Reciprocal Rank:   {:.2}\n\
+        ✅ Multiple Debug Notes:   {} ({:.1}%)",
         total_errors,
         tally_best_correct_std, percentage(tally_best_correct_std),
         tally_best_correct_all, percentage(tally_best_correct_all),
         tally_all_bad_std,      percentage(tally_all_bad_std),
         tally_all_bad_all,      percentage(tally_all_bad_all),
         tally_trivial_yes,      percentage(tally_trivial_yes),
+        mean(sum_precision),
+        mean(sum_f1),
+        mean(sum_size_weighted),
+        tally_top_1,            percentage(tally_top_1),
+        tally_top_3,            percentage(tally_top_3),
+        format!("Top-{} Blame Hit:", top_k), tally_top_k, percentage(tally_top_k),
+        if total_errors == 0 { 0.0 } else { sum_mrr / total_errors as f64 },
+        tally_multiple_notes,   percentage(tally_multiple_notes),
     );
 
     format!("{}\n{}", table, summary)
 }
+
+/// Generates a table comparing flux's suggested refinements against the
+/// annotated ones, with one row per fix line that has an annotated refinement.
+///
+/// # Arguments
+/// * `results`: A slice containing the evaluation results for multiple errors.
+///
+/// # Returns
+/// * A `String` containing the formatted table followed by summary lines.
+pub fn generate_refinement_table(results: &[ErrorEvalResult]) -> String {
+    let mut table = Table::new();
+    table
+        .load_preset(comfy_table::presets::UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "Error Name",
+            "Fix",
+            "Line",
+            "Annotated",
+            "Suggested",
+            "Exact?",
+            "Normalized?",
+            "Similarity",
+        ]);
+
+    let mut num_refinements = 0;
+    let mut num_suggested = 0;
+    let mut num_exact = 0;
+    let mut num_normalized = 0;
+    let mut sum_similarity = 0.0;
+
+    for result in results {
+        for (fix_index, eval) in result.fix_evals.iter().enumerate() {
+            for refinement in &eval.refinement_evals {
+                table.add_row(vec![
+                    Cell::new(&result.error_name),
+                    Cell::new(fix_index).set_alignment(comfy_table::CellAlignment::Right),
+                    Cell::new(format_line_locs(std::slice::from_ref(&refinement.line))),
+                    Cell::new(&refinement.expected),
+                    Cell::new(refinement.suggested.as_deref().unwrap_or("-")),
+                    Cell::new(if refinement.is_exact_match { "Yes" } else { "No" }),
+                    Cell::new(if refinement.is_normalized_match { "Yes" } else { "No" }),
+                    Cell::new(format!("{:.2}", refinement.similarity))
+                        .set_alignment(comfy_table::CellAlignment::Right),
+                ]);
+                num_refinements += 1;
+                if refinement.suggested.is_some() { num_suggested += 1; }
+                if refinement.is_exact_match { num_exact += 1; }
+                if refinement.is_normalized_match { num_normalized += 1; }
+                sum_similarity += refinement.similarity;
+            }
+        }
+    }
+
+    if num_refinements == 0 {
+        return "No annotated refinements to compare.".to_string();
+    }
+
+    let percentage = |count: usize| -> f64 { (count as f64 / num_refinements as f64) * 100.0 };
+    let summary = format!(
+        "--- Refinement Summary ({} Annotated Refinements) ---\n\
+        ✅ Suggestion Found:     {} ({:.1}%)\n\
+        ✅ Exact Match:          {} ({:.1}%)\n\
+        ✅ Normalized Match:     {} ({:.1}%)\n\
+        ✅ Mean Similarity:      {:.2}",
+        num_refinements,
+        num_suggested,  percentage(num_suggested),
+        num_exact,      percentage(num_exact),
+        num_normalized, percentage(num_normalized),
+        sum_similarity / num_refinements as f64,
+    );
+
+    format!("{}\n{}", table, summary)
+}
+
+/// Serializes the evaluation records as a pretty-printed JSON array.
+pub fn generate_json_report(records: &[EvalRecord], unmatched: &[UnmatchedError]) -> anyhow::Result<String> {
+    let report = EvalReport {
+        results: records.to_vec(),
+        unmatched: unmatched.to_vec(),
+    };
+    serde_json::to_string_pretty(&report)
+        .map_err(|e| anyhow::anyhow!("Failed to serialize eval results to JSON: {}", e))
+}
+
+/// The JSON eval report: the evaluated errors plus the ones that couldn't be
+/// evaluated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub results: Vec<EvalRecord>,
+    #[serde(default)]
+    pub unmatched: Vec<UnmatchedError>,
+}
+
+/// Generates a section per `UnmatchedReason` listing the errors that couldn't
+/// be evaluated, grouped by suite.
+///
+/// # Returns
+/// * `None` if every benchmark was matched with a flux error and vice versa.
+pub fn generate_unmatched_report(unmatched: &[UnmatchedError]) -> Option<String> {
+    if unmatched.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<&UnmatchedError> = unmatched.iter().collect();
+    sorted.sort_by(|a, b| {
+        (a.reason, &a.repo_name, &a.subdir, &a.commit, &a.error_name)
+            .cmp(&(b.reason, &b.repo_name, &b.subdir, &b.commit, &b.error_name))
+    });
+
+    let mut out = format!("--- Unmatched Errors ({} Total) ---", unmatched.len());
+    let mut current_reason = None;
+    let mut current_suite = None;
+    for error in sorted {
+        if current_reason != Some(error.reason) {
+            let count = unmatched.iter().filter(|e| e.reason == error.reason).count();
+            write!(out, "\n{} ({}):", error.reason, count).unwrap();
+            current_reason = Some(error.reason);
+            current_suite = None;
+        }
+        let suite = (&error.repo_name, &error.subdir, &error.commit);
+        if current_suite != Some(suite) {
+            write!(out, "\n  {}/{}/{}", error.repo_name, error.subdir.display(), error.commit).unwrap();
+            current_suite = Some(suite);
+        }
+        write!(out, "\n    - {}", error.error_name).unwrap();
+    }
+    Some(out)
+}
+
+/// Header of the CSV report. There is one row per evaluated `Fix`; errors
+/// without any fixes get a single row with the fix columns left empty, and
+/// so do unmatched errors (with `unmatched_reason` set).
+const CSV_HEADER: &[&str] = &[
+    "repo_name",
+    "subdir",
+    "commit",
+    "error_name",
+    "num_blamed",
+    "num_binders",
+    "num_debug_notes",
+    "fix_index",
+    "is_trivial",
+    "num_total_lines",
+    "num_correct_lines",
+    "num_correct_lines_all_binders",
+    "ratio",
+    "ratio_all_binders",
+    "status",
+    "status_all_binders",
+    "missing_lines",
+    "missing_lines_all_binders",
+    "num_blame_spans_hit",
+    "num_spurious_blame_spans",
+    "num_blamed_lines",
+    "precision",
+    "line_coverage",
+    "f1",
+    "first_hit_rank",
+    "mean_reciprocal_rank",
+    "line_ranks",
+    "num_refinements",
+    "num_refinements_exact",
+    "num_refinements_normalized",
+    "mean_refinement_similarity",
+    "size_weighted_ratio",
+    "hit_span_sizes",
+    "unmatched_reason",
+];
+
+/// Quotes a CSV field if it contains a separator, quote or newline.
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Formats line locations as `file:line` entries separated by `;`.
+fn format_line_locs(line_locs: &[LineLoc]) -> String {
+    line_locs
+        .iter()
+        .map(|loc| format!("{}:{}", loc.file.display(), loc.line))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Classifies a fix evaluation as `correct`, `partial`, `incorrect` or `empty`
+/// (no fix lines to find).
+fn fix_status(fully_correct: bool, partially_correct: bool, fully_incorrect: bool) -> &'static str {
+    if fully_correct {
+        "correct"
+    } else if partially_correct {
+        "partial"
+    } else if fully_incorrect {
+        "incorrect"
+    } else {
+        "empty"
+    }
+}
+
+/// Serializes the evaluation records as CSV with one row per evaluated fix.
+pub fn generate_csv_report(records: &[EvalRecord], unmatched: &[UnmatchedError]) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", CSV_HEADER.join(",")).unwrap();
+
+    for record in records {
+        let result = &record.result;
+        let prefix = [
+            record.repo_name.clone(),
+            record.subdir.display().to_string(),
+            record.commit.clone(),
+            result.error_name.clone(),
+            result.num_blamed.to_string(),
+            result.num_binders.to_string(),
+            result.num_debug_notes.to_string(),
+        ];
+
+        if result.fix_evals.is_empty() {
+            let mut row: Vec<String> = prefix.to_vec();
+            row.resize(CSV_HEADER.len(), String::new());
+            let row: Vec<String> = row.iter().map(|f| csv_escape(f)).collect();
+            writeln!(out, "{}", row.join(",")).unwrap();
+            continue;
+        }
+
+        for (fix_index, eval) in result.fix_evals.iter().enumerate() {
+            let mut row: Vec<String> = prefix.to_vec();
+            row.extend([
+                fix_index.to_string(),
+                eval.is_trivial.map_or(String::new(), |t| t.to_string()),
+                eval.num_total_lines.to_string(),
+                eval.num_correct_lines.to_string(),
+                eval.num_correct_lines_all_binders.to_string(),
+                format!("{:.4}", eval.ratio()),
+                format!("{:.4}", eval.ratio_all_binders()),
+                fix_status(
+                    eval.is_fully_correct(),
+                    eval.is_partially_correct(),
+                    eval.is_fully_incorrect(),
+                )
+                .to_string(),
+                fix_status(
+                    eval.is_fully_correct_all_binders(),
+                    eval.is_partially_correct_all_binders(),
+                    eval.is_fully_incorrect_all_binders(),
+                )
+                .to_string(),
+                format_line_locs(&eval.missing_lines),
+                format_line_locs(&eval.missing_lines_all_binders),
+                eval.num_blame_spans_hit.to_string(),
+                eval.num_spurious_blame_spans.to_string(),
+                eval.num_blamed_lines.to_string(),
+                format!("{:.4}", eval.precision()),
+                format!("{:.4}", eval.line_coverage()),
+                format!("{:.4}", eval.f1()),
+                eval.first_hit_rank().map_or(String::new(), |rank| rank.to_string()),
+                format!("{:.4}", eval.mean_reciprocal_rank()),
+                eval.line_ranks
+                    .iter()
+                    .map(|rank| rank.map_or("-".to_string(), |rank| rank.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(";"),
+                eval.refinement_evals.len().to_string(),
+                eval.num_refinements_exact().to_string(),
+                eval.num_refinements_normalized().to_string(),
+                eval.mean_refinement_similarity()
+                    .map_or(String::new(), |similarity| format!("{:.4}", similarity)),
+                format!("{:.4}", eval.size_weighted_ratio()),
+                eval.hit_span_sizes
+                    .iter()
+                    .map(|size| size.map_or("-".to_string(), |size| size.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(";"),
+                // unmatched_reason
+                String::new(),
+            ]);
+            let row: Vec<String> = row.iter().map(|f| csv_escape(f)).collect();
+            writeln!(out, "{}", row.join(",")).unwrap();
+        }
+    }
+
+    for error in unmatched {
+        let mut row = vec![
+            error.repo_name.clone(),
+            error.subdir.display().to_string(),
+            error.commit.clone(),
+            error.error_name.clone(),
+        ];
+        row.resize(CSV_HEADER.len() - 1, String::new());
+        row.push(error.reason.as_str().to_string());
+        let row: Vec<String> = row.iter().map(|f| csv_escape(f)).collect();
+        writeln!(out, "{}", row.join(",")).unwrap();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{tests::error_and_fixes, Fix};
+    use serde_json::json;
+
+    fn fix_eval() -> FixEvalResult {
+        FixEvalResult {
+            num_correct_lines: 0,
+            num_correct_lines_all_binders: 0,
+            missing_lines: vec![],
+            missing_lines_all_binders: vec![],
+            num_total_lines: 0,
+            is_trivial: None,
+            num_blame_spans_hit: 0,
+            num_spurious_blame_spans: 0,
+            num_blamed_lines: 0,
+            line_ranks: vec![],
+            refinement_evals: vec![],
+            hit_span_sizes: vec![],
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    /// A span in `file` from `start` to `end`, as (line, char) pairs.
+    fn span(file: &str, start: (usize, usize), end: (usize, usize)) -> serde_json::Value {
+        json!({
+            "start": { "line": start.0, "char": start.1, "file": file },
+            "end": { "line": end.0, "char": end.1, "file": file },
+        })
+    }
+
+    /// Debug info blaming `blame_spans` in order, without any binders.
+    fn constraint_info(blame_spans: Vec<Option<serde_json::Value>>) -> ConstraintDebugInfoDeserialize {
+        let blame_spans: Vec<serde_json::Value> = blame_spans
+            .into_iter()
+            .enumerate()
+            .map(|(i, span)| json!({ "binder_name": format!("a{}", i), "blame_span": span, "suggested_refinement": null }))
+            .collect();
+        serde_json::from_value(json!({ "constraint": "true", "binders": [], "blame_spans": blame_spans })).unwrap()
+    }
+
+    fn fix_line(file: &str, line: usize) -> FixLine {
+        FixLine { line, file: file.into(), added_reft: None, columns: None }
+    }
+
+    fn benchmark(fixes: Vec<Vec<FixLine>>) -> ErrorAndFixes {
+        let mut benchmark = error_and_fixes("error", "error");
+        benchmark.fixes = fixes
+            .into_iter()
+            .map(|fix_lines| Fix { fix_lines, note: None, is_trivial: None })
+            .collect();
+        benchmark
+    }
+
+    #[test]
+    fn ratio_is_the_fraction_of_fix_lines_found() {
+        let eval = FixEvalResult { num_correct_lines: 1, num_total_lines: 4, ..fix_eval() };
+        assert_close(eval.ratio(), 0.25);
+        // Nothing to find counts as perfect
+        assert_close(fix_eval().ratio(), 1.0);
+    }
+
+    #[test]
+    fn precision_is_the_fraction_of_blame_spans_hitting_the_fix() {
+        let eval = FixEvalResult { num_blame_spans_hit: 1, num_spurious_blame_spans: 3, ..fix_eval() };
+        assert_close(eval.precision(), 0.25);
+        // Nothing blamed
+        assert_close(fix_eval().precision(), 0.0);
+    }
+
+    #[test]
+    fn line_coverage_is_blamed_lines_per_fix_line() {
+        let eval = FixEvalResult { num_blamed_lines: 6, num_total_lines: 2, ..fix_eval() };
+        assert_close(eval.line_coverage(), 3.0);
+        assert_close(fix_eval().line_coverage(), 0.0);
+    }
+
+    #[test]
+    fn f1_is_the_harmonic_mean_of_precision_and_recall() {
+        let eval = FixEvalResult {
+            num_correct_lines: 1,
+            num_total_lines: 2,
+            num_blame_spans_hit: 1,
+            num_spurious_blame_spans: 0,
+            ..fix_eval()
+        };
+        assert_close(eval.f1(), 2.0 / 3.0);
+
+        let missed = FixEvalResult { num_total_lines: 2, num_spurious_blame_spans: 1, ..fix_eval() };
+        assert_close(missed.f1(), 0.0);
+    }
+
+    #[test]
+    fn evaluate_error_counts_hits_and_spurious_blame_spans() {
+        let constraint_info = constraint_info(vec![
+            Some(span("src/lib.rs", (9, 1), (11, 5))),
+            Some(span("src/lib.rs", (30, 1), (30, 5))),
+            // Spans without a location are left out of the precision metrics
+            None,
+        ]);
+        let benchmark = benchmark(vec![vec![fix_line("src/lib.rs", 10), fix_line("src/lib.rs", 20)]]);
+
+        let result = evaluate_error(&constraint_info, &benchmark, SpanMatching::Lines);
+        assert_eq!(result.num_blamed, 3);
+        let [eval] = result.fix_evals.as_slice() else { panic!("expected one fix eval") };
+        assert_eq!(eval.num_correct_lines, 1);
+        assert_eq!(eval.missing_lines, vec![LineLoc::new(20, "src/lib.rs".into())]);
+        assert_eq!(eval.num_blame_spans_hit, 1);
+        assert_eq!(eval.num_spurious_blame_spans, 1);
+        assert_eq!(eval.num_blamed_lines, 4);
+        assert_close(eval.precision(), 0.5);
+        assert_close(eval.line_coverage(), 2.0);
+    }
+
+    #[test]
+    fn first_hit_rank_is_the_best_rank_of_any_fix_line() {
+        let eval = FixEvalResult { line_ranks: vec![None, Some(3), Some(2)], ..fix_eval() };
+        assert_eq!(eval.first_hit_rank(), Some(2));
+        let missed = FixEvalResult { line_ranks: vec![None, None], ..fix_eval() };
+        assert_eq!(missed.first_hit_rank(), None);
+    }
+
+    #[test]
+    fn mean_reciprocal_rank_counts_missed_lines_as_zero() {
+        let eval = FixEvalResult { line_ranks: vec![Some(1), Some(4), None, Some(2)], ..fix_eval() };
+        assert_close(eval.mean_reciprocal_rank(), (1.0 + 0.25 + 0.0 + 0.5) / 4.0);
+        assert_close(fix_eval().mean_reciprocal_rank(), 0.0);
+    }
+
+    #[test]
+    fn evaluate_error_ranks_fix_lines_by_the_first_blame_span_containing_them() {
+        let constraint_info = constraint_info(vec![
+            Some(span("src/lib.rs", (30, 1), (30, 5))),
+            None,
+            Some(span("src/lib.rs", (10, 1), (20, 5))),
+            Some(span("src/lib.rs", (20, 1), (20, 5))),
+        ]);
+        let benchmark = benchmark(vec![
+            vec![fix_line("src/lib.rs", 20), fix_line("src/lib.rs", 40)],
+            vec![fix_line("src/lib.rs", 30)],
+        ]);
+
+        let result = evaluate_error(&constraint_info, &benchmark, SpanMatching::Lines);
+        assert_eq!(result.fix_evals[0].line_ranks, vec![Some(3), None]);
+        assert_eq!(result.fix_evals[1].line_ranks, vec![Some(1)]);
+        assert_eq!(result.best_first_hit_rank(), Some(1));
+        assert_close(result.best_mean_reciprocal_rank(), 1.0);
+    }
+
+    fn span_deserialize(file: &str, start: (usize, usize), end: (usize, usize)) -> SimpleSpanDeserialize {
+        serde_json::from_value(span(file, start, end)).unwrap()
+    }
+
+    #[test]
+    fn overlaps_columns_on_single_line_spans() {
+        let span = span_deserialize("src/lib.rs", (10, 5), (10, 9));
+        assert!(overlaps_columns(&span, 10, ColumnRange { start: 9, end: 12 }));
+        assert!(overlaps_columns(&span, 10, ColumnRange { start: 1, end: 5 }));
+        assert!(overlaps_columns(&span, 10, ColumnRange { start: 6, end: 7 }));
+        assert!(!overlaps_columns(&span, 10, ColumnRange { start: 10, end: 12 }));
+        assert!(!overlaps_columns(&span, 10, ColumnRange { start: 1, end: 4 }));
+    }
+
+    #[test]
+    fn overlaps_columns_on_multi_line_spans() {
+        // Starts at column 20 of line 10 and ends at column 3 of line 12
+        let span = span_deserialize("src/lib.rs", (10, 20), (12, 3));
+        assert!(!overlaps_columns(&span, 10, ColumnRange { start: 1, end: 19 }));
+        assert!(overlaps_columns(&span, 10, ColumnRange { start: 25, end: 30 }));
+        assert!(overlaps_columns(&span, 11, ColumnRange { start: 1, end: 1 }));
+        assert!(overlaps_columns(&span, 11, ColumnRange { start: 100, end: 120 }));
+        assert!(overlaps_columns(&span, 12, ColumnRange { start: 1, end: 3 }));
+        assert!(!overlaps_columns(&span, 12, ColumnRange { start: 4, end: 10 }));
+    }
+
+    #[test]
+    fn in_fix_line_only_checks_columns_with_column_matching() {
+        let span = span_deserialize("src/lib.rs", (10, 5), (10, 9));
+        let mut line = fix_line("src/lib.rs", 10);
+        assert!(in_fix_line(&span, &line, SpanMatching::Lines));
+        // Without a column range, the whole line counts either way
+        assert!(in_fix_line(&span, &line, SpanMatching::Columns));
+
+        line.columns = Some(ColumnRange { start: 12, end: 15 });
+        assert!(in_fix_line(&span, &line, SpanMatching::Lines));
+        assert!(!in_fix_line(&span, &line, SpanMatching::Columns));
+
+        line.columns = Some(ColumnRange { start: 8, end: 15 });
+        assert!(in_fix_line(&span, &line, SpanMatching::Columns));
+    }
+
+    #[test]
+    fn in_fix_line_needs_the_same_file_and_a_line_in_the_span() {
+        let span = span_deserialize("src/lib.rs", (10, 1), (12, 1));
+        assert!(in_fix_line(&span, &fix_line("src/lib.rs", 12), SpanMatching::Lines));
+        assert!(in_fix_line(&span, &fix_line("./src/lib.rs", 11), SpanMatching::Lines));
+        assert!(!in_fix_line(&span, &fix_line("src/lib.rs", 13), SpanMatching::Lines));
+        assert!(!in_fix_line(&span, &fix_line("src/main.rs", 11), SpanMatching::Lines));
+    }
+
+    #[test]
+    fn size_weighted_ratio_weighs_hits_by_the_smallest_span_size() {
+        let eval = FixEvalResult { hit_span_sizes: vec![Some(1), Some(4), None], ..fix_eval() };
+        assert_close(eval.size_weighted_ratio(), (1.0 + 0.25) / 3.0);
+        assert_close(fix_eval().size_weighted_ratio(), 0.0);
+    }
+
+    #[test]
+    fn evaluate_error_with_column_matching() {
+        let constraint_info = constraint_info(vec![
+            Some(span("src/lib.rs", (10, 1), (13, 5))),
+            Some(span("src/lib.rs", (10, 5), (10, 9))),
+        ]);
+        let mut on_span = fix_line("src/lib.rs", 10);
+        on_span.columns = Some(ColumnRange { start: 7, end: 8 });
+        let mut next_to_span = fix_line("src/lib.rs", 13);
+        next_to_span.columns = Some(ColumnRange { start: 6, end: 8 });
+        let benchmark = benchmark(vec![vec![on_span, next_to_span]]);
+
+        let lines = evaluate_error(&constraint_info, &benchmark, SpanMatching::Lines);
+        assert_eq!(lines.fix_evals[0].line_ranks, vec![Some(1), Some(1)]);
+        assert_eq!(lines.fix_evals[0].hit_span_sizes, vec![Some(1), Some(4)]);
+
+        let columns = evaluate_error(&constraint_info, &benchmark, SpanMatching::Columns);
+        assert_eq!(columns.fix_evals[0].line_ranks, vec![Some(1), None]);
+        assert_eq!(columns.fix_evals[0].num_correct_lines, 1);
+    }
+}
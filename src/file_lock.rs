@@ -0,0 +1,73 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+use tempfile::NamedTempFile;
+
+/// An advisory lock held by creating a lock file, so that it also excludes
+/// other processes (e.g. two annotators working on the same bench root).
+/// The lock file is removed when this is dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Creates the lock file at `path`, failing if another session holds it.
+    ///
+    /// # Arguments
+    /// * `path`: The lock file.
+    /// * `what`: What is being locked, for the error message.
+    pub fn acquire(path: &Path, what: &str) -> Result<Self> {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                // Record who holds the lock for the error message of others
+                writeln!(file, "{}", std::process::id())
+                    .with_context(|| format!("Failed to write lock file {:?}", path))?;
+                Ok(Self {
+                    path: path.to_path_buf(),
+                })
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let holder = fs::read_to_string(path)
+                    .map(|pid| format!("process {}", pid.trim()))
+                    .unwrap_or_else(|_| "an unknown process".to_string());
+                bail!(
+                    "{} is locked by another session ({}). If no other session is running, remove the stale lock file {:?}.",
+                    what,
+                    holder,
+                    path
+                )
+            }
+            Err(e) => Err(e).with_context(|| format!("Failed to create lock file {:?}", path)),
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            eprintln!("Warning: Failed to remove lock file {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Writes `contents` to `path` by writing a temporary file next to it and
+/// renaming it over `path`, so readers never see a partially written file.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let dir = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let mut temp_file = NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temporary file in {:?}", dir))?;
+    temp_file
+        .write_all(contents.as_ref())
+        .with_context(|| format!("Failed to write temporary file for {:?}", path))?;
+    temp_file
+        .persist(path)
+        .with_context(|| format!("Failed to move temporary file to {:?}", path))?;
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::file_lock::write_atomic;
+use crate::types::GitInformation;
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the file (inside a recording directory) holding the version of
+/// flux that produced the recordings.
+const FLUX_VERSION_FILE: &str = "flux-version.txt";
+
+/// Name of the file holding the raw flux output of a single suite.
+const RECORDING_FILE: &str = "flux.jsonl";
+
+/// Returns the directory that recordings made with `flux_version` are stored
+/// in under `record_root`. This is the directory to pass to `eval --replay`.
+pub fn version_dir(record_root: &Path, flux_version: &str) -> PathBuf {
+    // Versions look like `flux 0.1.0 (abc1234 2025-01-01)`, so keep only the
+    // characters that are safe in a directory name.
+    let sanitized = flux_version
+        .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    record_root.join(sanitized)
+}
+
+/// Returns where the raw flux output of the suite described by `git_info` is
+/// stored in `recording_dir`. The layout mirrors the bench tree.
+fn recording_path(recording_dir: &Path, git_info: &GitInformation) -> PathBuf {
+    recording_dir
+        .join(&git_info.repo_name)
+        .join(&git_info.subdir)
+        .join(&git_info.commit)
+        .join(RECORDING_FILE)
+}
+
+/// Creates `recording_dir` and notes the flux version in it.
+pub fn init_recording_dir(recording_dir: &Path, flux_version: &str) -> Result<()> {
+    fs::create_dir_all(recording_dir)
+        .with_context(|| format!("Failed to create recording directory: {:?}", recording_dir))?;
+    let version_path = recording_dir.join(FLUX_VERSION_FILE);
+    write_atomic(&version_path, format!("{}\n", flux_version))
+        .with_context(|| format!("Failed to write flux version to {:?}", version_path))
+}
+
+/// Reads the flux version a recording directory was made with, if noted.
+pub fn recorded_flux_version(recording_dir: &Path) -> Option<String> {
+    fs::read_to_string(recording_dir.join(FLUX_VERSION_FILE))
+        .ok()
+        .map(|version| version.trim().to_string())
+}
+
+/// Saves the raw JSON lines flux printed for a suite.
+pub fn save_recording(recording_dir: &Path, git_info: &GitInformation, lines: &[String]) -> Result<PathBuf> {
+    let path = recording_path(recording_dir, git_info);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create recording directory: {:?}", parent))?;
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    write_atomic(&path, content).with_context(|| format!("Failed to write flux recording to {:?}", path))?;
+    Ok(path)
+}
+
+/// Loads the raw JSON lines recorded for a suite.
+pub fn load_recording(recording_dir: &Path, git_info: &GitInformation) -> Result<Vec<String>> {
+    let path = recording_path(recording_dir, git_info);
+    if !path.is_file() {
+        bail!("No flux recording for this suite at {:?}", path);
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read flux recording: {:?}", path))?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_dir_keeps_only_safe_characters() {
+        assert_eq!(
+            version_dir(Path::new("recordings"), "flux 0.1.0 (abc1234 2025-01-01)\n"),
+            Path::new("recordings/flux_0.1.0_abc1234_2025-01-01")
+        );
+        assert_eq!(version_dir(Path::new("recordings"), "../../etc"), Path::new("recordings/.._.._etc"));
+    }
+}
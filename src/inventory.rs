@@ -0,0 +1,228 @@
+use crate::benchmark_processor::BenchmarkArgs;
+use crate::benchmark_suite::BenchmarkSuite;
+use crate::types::ErrorAndFixes;
+use anyhow::{Context, Result};
+use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The annotation summary of a single benchmark.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorListing {
+    pub error_name: String,
+    pub num_fixes: usize,
+    /// Total over all of the fixes
+    pub num_fix_lines: usize,
+    /// `Some(true)` if any fix is marked trivial, `Some(false)` if fixes are
+    /// marked but none is trivial, `None` if no fix is marked.
+    pub is_trivial: Option<bool>,
+    pub has_note: bool,
+}
+
+impl ErrorListing {
+    pub fn new(benchmark: &ErrorAndFixes) -> Self {
+        let fixes = &benchmark.fixes;
+        let is_trivial = if fixes.iter().any(|fix| fix.is_trivial == Some(true)) {
+            Some(true)
+        } else if fixes.iter().any(|fix| fix.is_trivial.is_some()) {
+            Some(false)
+        } else {
+            None
+        };
+        Self {
+            error_name: benchmark.error_name.clone(),
+            num_fixes: fixes.len(),
+            num_fix_lines: fixes.iter().map(|fix| fix.fix_lines.len()).sum(),
+            is_trivial,
+            has_note: fixes.iter().any(|fix| fix.note.is_some()),
+        }
+    }
+
+    /// Whether any fix line was annotated
+    pub fn is_annotated(&self) -> bool {
+        self.num_fix_lines > 0
+    }
+}
+
+/// The contents of a single suite.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuiteListing {
+    pub repo_name: String,
+    pub subdir: PathBuf,
+    pub commit: String,
+    pub has_git_info: bool,
+    /// Sorted by error name
+    pub errors: Vec<ErrorListing>,
+    /// Why the benchmarks couldn't be loaded, if they couldn't
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_error: Option<String>,
+}
+
+impl SuiteListing {
+    /// Lists the benchmarks of `suite` that match the benchmark filters.
+    pub fn new(suite: &BenchmarkSuite, filters: &BenchmarkArgs) -> Self {
+        let (errors, load_error) = match suite.load_benchmarks() {
+            Ok(benchmarks) => {
+                let mut errors: Vec<ErrorListing> = benchmarks
+                    .iter()
+                    .filter(|benchmark| filters.matches_benchmark(benchmark))
+                    .map(ErrorListing::new)
+                    .collect();
+                errors.sort_by(|a, b| a.error_name.cmp(&b.error_name));
+                (errors, None)
+            }
+            Err(e) => (vec![], Some(format!("{:#}", e))),
+        };
+        Self {
+            repo_name: suite.repo_name().to_string(),
+            subdir: PathBuf::from(suite.subdir_name()),
+            commit: suite.commit_hash().to_string(),
+            has_git_info: suite.git_info().is_some(),
+            errors,
+            load_error,
+        }
+    }
+
+    pub fn num_fixes(&self) -> usize {
+        self.errors.iter().map(|error| error.num_fixes).sum()
+    }
+
+    pub fn num_fix_lines(&self) -> usize {
+        self.errors.iter().map(|error| error.num_fix_lines).sum()
+    }
+}
+
+/// Counts over all listed suites.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListTotals {
+    pub num_suites: usize,
+    pub num_suites_without_git_info: usize,
+    pub num_errors: usize,
+    pub num_annotated_errors: usize,
+    pub num_fixes: usize,
+    pub num_fix_lines: usize,
+    pub num_trivial_errors: usize,
+    pub num_errors_with_note: usize,
+}
+
+impl ListTotals {
+    pub fn new(suites: &[SuiteListing]) -> Self {
+        let mut totals = Self {
+            num_suites: suites.len(),
+            ..Self::default()
+        };
+        for suite in suites {
+            if !suite.has_git_info {
+                totals.num_suites_without_git_info += 1;
+            }
+            for error in &suite.errors {
+                totals.num_errors += 1;
+                totals.num_annotated_errors += usize::from(error.is_annotated());
+                totals.num_fixes += error.num_fixes;
+                totals.num_fix_lines += error.num_fix_lines;
+                totals.num_trivial_errors += usize::from(error.is_trivial == Some(true));
+                totals.num_errors_with_note += usize::from(error.has_note);
+            }
+        }
+        totals
+    }
+}
+
+/// The JSON form of the listing.
+#[derive(Serialize)]
+struct ListReport<'a> {
+    suites: &'a [SuiteListing],
+    totals: ListTotals,
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Generates a table with a row for every suite, followed by rows for each of
+/// its errors, and the totals underneath.
+pub fn generate_list_table(suites: &[SuiteListing]) -> String {
+    if suites.is_empty() {
+        return "No benchmark suites found.".to_string();
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(comfy_table::presets::UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "Repo",
+            "Subdir",
+            "Commit",
+            "Error Name",
+            "Fixes",
+            "Fix Lines",
+            "Trivial?",
+            "Note?",
+            "Git Info?",
+        ]);
+
+    let right = |value: usize| Cell::new(value).set_alignment(CellAlignment::Right);
+    let center = |value: &str| Cell::new(value).set_alignment(CellAlignment::Center);
+    for suite in suites {
+        let errors_cell = match &suite.load_error {
+            Some(e) => format!("(failed to load: {})", e),
+            None => format!("({} errors)", suite.errors.len()),
+        };
+        table.add_row(vec![
+            Cell::new(&suite.repo_name),
+            Cell::new(suite.subdir.display()),
+            Cell::new(&suite.commit[..7.min(suite.commit.len())]),
+            Cell::new(errors_cell),
+            right(suite.num_fixes()),
+            right(suite.num_fix_lines()),
+            center(""),
+            center(""),
+            center(yes_no(suite.has_git_info)),
+        ]);
+        for error in &suite.errors {
+            let trivial = match error.is_trivial {
+                Some(is_trivial) => yes_no(is_trivial),
+                None => "-",
+            };
+            table.add_row(vec![
+                Cell::new(""),
+                Cell::new(""),
+                Cell::new(""),
+                Cell::new(&error.error_name),
+                right(error.num_fixes),
+                right(error.num_fix_lines),
+                center(trivial),
+                center(yes_no(error.has_note)),
+                center(""),
+            ]);
+        }
+    }
+
+    let totals = ListTotals::new(suites);
+    format!(
+        "{}\nTotals: {} suites ({} without git-info.json), {} errors ({} annotated, {} trivial, {} with notes), {} fixes, {} fix lines",
+        table,
+        totals.num_suites,
+        totals.num_suites_without_git_info,
+        totals.num_errors,
+        totals.num_annotated_errors,
+        totals.num_trivial_errors,
+        totals.num_errors_with_note,
+        totals.num_fixes,
+        totals.num_fix_lines,
+    )
+}
+
+/// Generates a JSON object with the listed suites and their totals.
+pub fn generate_json_list(suites: &[SuiteListing]) -> Result<String> {
+    let report = ListReport {
+        suites,
+        totals: ListTotals::new(suites),
+    };
+    serde_json::to_string_pretty(&report).context("Failed to serialize the benchmark list")
+}
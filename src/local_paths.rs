@@ -1,3 +1,4 @@
+use crate::file_lock::{write_atomic, FileLock};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -54,22 +55,25 @@ impl LocalPathResolver {
         })
     }
 
-    /// Saves the current configuration back to the file.
-    /// Acquires a lock to prevent concurrent writes.
-    pub fn save(&self) -> Result<()> {
-        let _guard = CONFIG_LOCK.lock().unwrap(); // Lock before writing
-
-        let content = toml::to_string_pretty(&self.config)
-            .context("Failed to serialize local paths config to TOML")?;
-
+    /// Locks the config file at `config_path` against other sessions (processes).
+    fn lock_file(config_path: &Path) -> Result<FileLock> {
         // Ensure parent directory exists
-        if let Some(parent) = self.config_path.parent() {
+        if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent).with_context(|| {
                 format!("Failed to create parent directory for config: {:?}", parent)
             })?;
         }
+        let mut lock_path = config_path.as_os_str().to_os_string();
+        lock_path.push(".lock");
+        FileLock::acquire(Path::new(&lock_path), &format!("{:?}", config_path))
+    }
+
+    /// Writes the config, which the caller must have locked.
+    fn write_config(&self) -> Result<()> {
+        let content = toml::to_string_pretty(&self.config)
+            .context("Failed to serialize local paths config to TOML")?;
 
-        fs::write(&self.config_path, content).with_context(|| {
+        write_atomic(&self.config_path, content).with_context(|| {
             format!(
                 "Failed to write local paths config to {:?}",
                 self.config_path
@@ -124,12 +128,15 @@ impl LocalPathResolver {
         commit_hash: &str,
         local_path: &Path,
     ) -> Result<()> {
+        let _guard = CONFIG_LOCK.lock().unwrap();
+        // Hold the lock from loading to saving so no other session's update is lost
+        let _file_lock = Self::lock_file(config_path)?;
         // Load the latest config
         let mut resolver = Self::load(config_path.to_path_buf())?;
         // Add the new override
         resolver.add_commit_override(repo_name, commit_hash, local_path);
         // Save it back
-        resolver.save()
+        resolver.write_config()
     }
 
     pub fn config_path(&self) -> &Path {
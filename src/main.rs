@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Context, Result};
-use cached_repository::GitWorktreeDir;
+use cached_repository::{CachedRepository, GitWorktreeDir};
 // Added Context
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
@@ -9,25 +9,42 @@ use crossterm::{
 use ratatui::prelude::*;
 use std::{
     collections::HashMap,
+    fmt::Write,
     fs,
     io::stdout,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
 };
 
+mod baseline;
 mod benchmark_processor;
 mod benchmark_suite;
 mod cached_repository;
+mod flux_recording;
+mod inventory;
 mod local_paths;
+mod port;
 mod run_cmd;
 mod tui;
 mod types;
+mod validate;
 mod evaluator;
+mod file_lock;
 
-use benchmark_processor::{process_benchmarks, BenchmarkArgs};
+use benchmark_processor::{
+    discover_suites, generate_failure_report, process_benchmarks, BenchmarkArgs, FailureStage,
+    ProcessOptions, SuiteFailure,
+};
 use benchmark_suite::BenchmarkSuite;
+use evaluator::{
+    ConstraintDebugInfoDeserialize, ErrorEvalResult, EvalRecord, SpanMatching, SpanPathResolver, UnmatchedError, UnmatchedReason,
+};
 use local_paths::LocalPathResolver;
-use tui::{run_app, AppState, ExitIntent};
-use types::{ErrorAndFixes, GitInformation};
+use tui::{run_app, AppState, ErrorStatus, ExitIntent, SessionError};
+use types::{match_errors, ErrorAndFixes, Fix, GitInformation};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -50,6 +67,14 @@ enum Command {
     Edit(EditArgs),
     /// Evaluate benchmarks specified by filters by running `flux`
     Eval(EvalArgs),
+    /// Compare two eval runs (saved baselines or JSON reports) and fail on regressions
+    Compare(CompareArgs),
+    /// List the benchmark suites and errors specified by filters
+    List(ListArgs),
+    /// Check benchmarks specified by filters for inconsistencies with their source
+    Validate(ValidateArgs),
+    /// Carry the annotated fixes of benchmarks over to a newer commit of the same repo
+    Port(PortArgs),
 }
 
 impl Command {
@@ -73,8 +98,9 @@ impl Command {
         let canonical_bench_root = bench_root.canonicalize().unwrap_or_else(|_| bench_root.clone());
         let canonical_cache_root = cache_root.canonicalize().unwrap_or_else(|_| cache_root.clone());
 
-        println!("Using benchmark root: {:?}", canonical_bench_root);
-        println!("Using cache root: {:?}", canonical_cache_root);
+        // On stderr, so reports printed to stdout (e.g. `list --format json`) stay parsable
+        eprintln!("Using benchmark root: {:?}", canonical_bench_root);
+        eprintln!("Using cache root: {:?}", canonical_cache_root);
 
 
         match self {
@@ -82,6 +108,10 @@ impl Command {
             Self::Add(args) => args.run(local_resolver, bench_root, &cache_root),
             Self::Edit(args) => args.run(local_resolver, bench_root, &cache_root),
             Self::Eval(args) => args.run(local_resolver, bench_root, &cache_root),
+            Self::Compare(args) => args.run(&bench_root),
+            Self::List(args) => args.run(&bench_root),
+            Self::Validate(args) => args.run(local_resolver, bench_root, &cache_root),
+            Self::Port(args) => args.run(local_resolver, bench_root, &cache_root),
         }
     }
 }
@@ -103,10 +133,14 @@ struct AddArgs {
     /// Use a persistent cached worktree instead of a temporary one (useful for build caching during editing).
     #[arg(long, default_value_t = false)] // Default is temporary for 'add'
     cache: bool,
+    /// Run flux with `-Fdebug-binder-output` and overlay what it blamed for
+    /// each error in the TUI (toggle with `b`).
+    #[arg(long)]
+    blame: bool,
 }
 
 impl AddArgs {
-    fn run(&self, local_resolver: LocalPathResolver, bench_root: PathBuf, cache_root: &Path) -> Result<()> {
+    fn run(&self, local_resolver: LocalPathResolver, bench_root: PathBuf, _cache_root: &Path) -> Result<()> {
          // Use absolute path for the input directory for consistency
          let absolute_dir = self.dir.canonicalize().with_context(|| format!("Failed to find or access input directory: {:?}", self.dir))?;
          println!("Processing add command for directory: {:?}", absolute_dir);
@@ -120,8 +154,8 @@ impl AddArgs {
         println!("Discovered Git info: {}", git_info);
 
         // Run flux in the *user-provided* directory.
-        // Pass false to disable debug info for add? Maybe not needed.
-        let errors_and_fixes = run_cmd::run_flux_in_dir(&absolute_dir, &git_info.commit, false)?;
+        // Debug info is only needed for the blame overlay.
+        let errors_and_fixes = run_cmd::run_flux_in_dir(&absolute_dir, &git_info.commit, self.blame)?;
         if errors_and_fixes.is_empty() {
              println!("No Flux errors found in {}. Nothing to add.", absolute_dir.display());
              return Ok(());
@@ -136,6 +170,8 @@ impl AddArgs {
             &git_info.commit,
         )?;
         println!("Target benchmark suite path: {:?}", suite.path());
+        // Keep other sessions from writing the suite until the TUI is done
+        suite.lock()?;
 
         if self.overwrite_existing && !self.edit_existing {
             return Err(anyhow!(
@@ -147,32 +183,40 @@ impl AddArgs {
         let existing_benchmarks = suite.load_benchmarks().unwrap_or_default();
         println!("Existing benchmarks found in suite: {}", existing_benchmarks.len());
 
-        // Create a map for quick lookup
-        let existing_benchmarks_map: HashMap<_,_> = existing_benchmarks
-            .into_iter()
-            .map(|b| (b.error_name.clone(), b))
-            .collect();
-
+        // Match the new errors with the existing benchmarks by fingerprint
+        // (falling back to the name), so errors that merely moved keep their fixes.
+        let existing_matches = match_errors(&errors_and_fixes, &existing_benchmarks);
+        let mut matched_existing = vec![false; existing_benchmarks.len()];
 
-        for error_and_fixes in errors_and_fixes {
-             let existing_benchmark_opt = existing_benchmarks_map.get(&error_and_fixes.error_name);
+        for (error_and_fixes, existing_idx) in errors_and_fixes.into_iter().zip(existing_matches) {
+            let existing_benchmark_opt = existing_idx.map(|idx| &existing_benchmarks[idx]);
+            if let Some(idx) = existing_idx {
+                matched_existing[idx] = true;
+            }
 
             match existing_benchmark_opt {
                 Some(existing_benchmark) => {
+                    if existing_benchmark.error_name != error_and_fixes.error_name {
+                        println!(
+                            "Matched {} to existing benchmark {} by fingerprint.",
+                            error_and_fixes.error_name, existing_benchmark.error_name
+                        );
+                    }
                     if self.edit_existing {
-                        println!("Editing existing benchmark: {}", error_and_fixes.error_name);
+                        println!("Editing existing benchmark: {}", existing_benchmark.error_name);
+                        // Keep the existing name so the benchmark file stays the same
+                        let mut merged = error_and_fixes.clone(); // Clone the new error data
+                        merged.error_name = existing_benchmark.error_name.clone();
                         if self.overwrite_existing {
-                             println!("  Overwriting previous fixes for {}.", error_and_fixes.error_name);
-                            updated_errors_and_fixes.push(error_and_fixes); // Use the new error/fix data
+                             println!("  Overwriting previous fixes for {}.", merged.error_name);
                         } else {
-                            println!("  Merging existing fixes for {}.", error_and_fixes.error_name);
+                            println!("  Merging existing fixes for {}.", merged.error_name);
                             // Keep existing fixes, but use the potentially updated error message/spans from the new run
-                            let mut merged = error_and_fixes.clone(); // Clone the new error data
                             merged.fixes = existing_benchmark.fixes.clone(); // Keep the old fixes
-                            updated_errors_and_fixes.push(merged);
                         }
+                        updated_errors_and_fixes.push(merged);
                     } else {
-                        println!("Skipping existing benchmark (use --edit-existing to modify): {}", error_and_fixes.error_name);
+                        println!("Skipping existing benchmark (use --edit-existing to modify): {}", existing_benchmark.error_name);
                     }
                 }
                 None => {
@@ -185,14 +229,22 @@ impl AddArgs {
 
         // Ensure benchmarks not found in the current run but existing previously are preserved unless overwritten
          if !self.overwrite_existing && self.edit_existing {
-             for (name, existing_benchmark) in existing_benchmarks_map {
-                 if !updated_errors_and_fixes.iter().any(|e| e.error_name == name) {
-                     println!("Preserving existing benchmark not found in current run: {}", name);
+             for (existing_benchmark, matched) in existing_benchmarks.into_iter().zip(matched_existing) {
+                 if !matched {
+                     println!("Preserving existing benchmark not found in current run: {}", existing_benchmark.error_name);
                      updated_errors_and_fixes.push(existing_benchmark);
                  }
              }
          }
 
+        // The debug info is only shown, never saved with the benchmarks
+        let constraint_infos = if self.blame {
+            let path_resolver = SpanPathResolver::new(&repo_path, &git_info.subdir);
+            take_constraint_infos(&mut updated_errors_and_fixes, &path_resolver)
+        } else {
+            HashMap::new()
+        };
+
         if updated_errors_and_fixes.is_empty() {
              println!("No benchmarks to add or edit after processing. Saving only git-info.");
              // Ensure suite dir exists and write git-info
@@ -206,6 +258,7 @@ impl AddArgs {
                 &git_info,
                 suite, // Pass suite for saving
                 updated_errors_and_fixes,
+                constraint_infos,
             )?;
         }
 
@@ -235,6 +288,10 @@ struct EditArgs {
     /// Use a persistent cached worktree instead of a temporary one (useful for build caching during editing).
     #[arg(long, default_value_t = false)] // Default is temporary for 'edit'
     cache: bool,
+    /// Run flux with `-Fdebug-binder-output` in the worktree and overlay what
+    /// it blamed for each benchmark in the TUI (toggle with `b`).
+    #[arg(long)]
+    blame: bool,
 }
 
 impl EditArgs {
@@ -255,22 +312,6 @@ impl EditArgs {
             println!("Editing suite: {:?}", suite.path());
             println!("Worktree path: {:?}", worktree.path());
 
-            // 1. Load existing benchmarks from the suite
-            let mut all_benchmarks_in_suite = suite.load_benchmarks().with_context(|| {
-                format!("Failed to load benchmarks from suite: {:?}", suite.path())
-            })?;
-
-            // 2. Apply error name filters *within* the suite
-            if !self.benchmarks.errors.is_empty() {
-                all_benchmarks_in_suite.retain(|b| self.benchmarks.errors.contains(&b.error_name));
-                println!("  Applied error filter, {} benchmarks remain.", all_benchmarks_in_suite.len());
-            }
-
-            if all_benchmarks_in_suite.is_empty() {
-                 println!("  No benchmarks in this suite match the error filters. Skipping TUI.");
-                 return Ok(());
-            }
-
             // We need GitInformation to save potentially updated info and for context
             let git_info = suite.git_info().ok_or_else(|| {
                 anyhow!(
@@ -287,33 +328,91 @@ impl EditArgs {
                  &git_info.subdir,
                  &git_info.commit,
              )?;
+            // Lock before loading, so no other session writes the suite
+            // between loading it and saving the edits
+            mutable_suite.lock()?;
+
+            // 1. Load existing benchmarks from the suite
+            let mut all_benchmarks_in_suite = mutable_suite.load_benchmarks().with_context(|| {
+                format!("Failed to load benchmarks from suite: {:?}", suite.path())
+            })?;
+
+            // 2. Apply benchmark filters *within* the suite
+            if self.benchmarks.filters_benchmarks() {
+                all_benchmarks_in_suite.retain(|b| self.benchmarks.matches_benchmark(b));
+                println!("  Applied benchmark filters, {} benchmarks remain.", all_benchmarks_in_suite.len());
+            }
+
+            if all_benchmarks_in_suite.is_empty() {
+                 println!("  No benchmarks in this suite match the filters. Skipping TUI.");
+                 return Ok(());
+            }
 
              // The TUI context should be the relevant subdirectory *within* the worktree
             let tui_context_path = worktree.path().join(&git_info.subdir);
             println!("Editing {} benchmarks in TUI context: {:?}", all_benchmarks_in_suite.len(), tui_context_path);
 
+            let constraint_infos = if self.blame {
+                println!("  Running flux with -Fdebug-binder-output for the blame overlay...");
+                let mut flux_errors = run_cmd::run_flux_in_dir(&tui_context_path, &git_info.commit, true)?;
+                let path_resolver = SpanPathResolver::new(worktree.path(), &git_info.subdir);
+                let mut infos = take_constraint_infos(&mut flux_errors, &path_resolver);
+                // Key the debug info by the benchmark each flux error matches
+                match_errors(&flux_errors, &all_benchmarks_in_suite)
+                    .into_iter()
+                    .zip(&flux_errors)
+                    .filter_map(|(benchmark_idx, error)| {
+                        let info = infos.remove(&error.error_name)?;
+                        Some((all_benchmarks_in_suite[benchmark_idx?].error_name.clone(), info))
+                    })
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
 
             run_tui_editor(
                 &tui_context_path,
                 git_info, // Existing git info from the suite
                 mutable_suite, // Pass the mutable suite
                 all_benchmarks_in_suite, // The filtered benchmarks to edit
+                constraint_infos,
             )?;
 
             Ok(())
         };
 
-        process_benchmarks(
+        let failures = process_benchmarks(
             &bench_root,
             &self.benchmarks,
             &local_resolver,
             cache_root, // Pass cache_root
-            use_cache, // Pass cache preference
+            ProcessOptions {
+                use_cache,
+                jobs: 1, // The TUI can only edit one suite at a time
+                keep_going: false,
+            },
             edit_action,
-        )
+        )?;
+        if !failures.is_empty() {
+            println!("{}", generate_failure_report(&failures));
+        }
+        Ok(())
     }
 }
 
+/// Format of the report produced by `eval`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable summary table
+    #[default]
+    Table,
+    /// One JSON object per evaluated error, including suite metadata
+    Json,
+    /// One CSV row per evaluated fix, including suite metadata
+    Csv,
+}
+
 #[derive(Args, Clone)]
 struct EvalArgs {
     #[command(flatten)]
@@ -321,6 +420,48 @@ struct EvalArgs {
     /// Use a temporary worktree instead of the default persistent cached one (saves disk space).
     #[arg(long, default_value_t = false)] // Default is cached for 'eval'
     no_cache: bool,
+    /// Format of the eval report. The summary table is always printed.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    output_format: OutputFormat,
+    /// Write the eval report to this file instead of stdout.
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Save the results of this run as a named baseline under the bench root
+    /// (for use with `compare`).
+    #[arg(long)]
+    save_baseline: Option<String>,
+    /// Number of suites to evaluate at the same time.
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+    /// Keep evaluating the remaining suites when one fails, and fail the run
+    /// if the failure report at the end isn't empty.
+    #[arg(short, long)]
+    keep_going: bool,
+    /// Report how often one of the first K blame spans hits a fix (in
+    /// addition to the top-1 and top-3 hit rates).
+    #[arg(long, value_name = "K", default_value_t = 5)]
+    top_k: usize,
+    /// Also compare flux's suggested refinements against the annotated ones
+    /// and print a per-line refinement table.
+    #[arg(long)]
+    refinements: bool,
+    /// Require blame spans to overlap a fix line's columns (for fix lines
+    /// annotated with a column range) instead of just containing the line.
+    #[arg(long)]
+    strict_columns: bool,
+    /// Save the raw flux output of every suite under this directory (in a
+    /// subdirectory named after the flux version), for use with `--replay`.
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Re-score the flux output recorded with `--record` instead of running
+    /// flux. DIR is the flux version subdirectory of the recording.
+    #[arg(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
+    /// Open the TUI on every evaluated error of each suite, showing which fix
+    /// lines the blame spans hit (`n` jumps to the next miss). Quitting stops
+    /// reviewing the remaining suites. Requires `--jobs 1`.
+    #[arg(short, long)]
+    interactive: bool,
 }
 
 impl EvalArgs {
@@ -331,95 +472,697 @@ impl EvalArgs {
         bench_root: PathBuf,
         cache_root: &Path,
     ) -> Result<()> {
-        println!("Running Eval command...");
+        // A JSON/CSV report on stdout has to be the only thing there, so
+        // everything else goes to stderr then
+        let report_on_stdout = self.output.is_none() && self.output_format != OutputFormat::Table;
+        macro_rules! status {
+            ($($arg:tt)*) => {
+                if report_on_stdout {
+                    eprintln!($($arg)*)
+                } else {
+                    println!($($arg)*)
+                }
+            };
+        }
+        status!("Running Eval command...");
         let use_cache = !self.no_cache; // Eval defaults to using cache unless --no-cache is given
-        let mut evaluations = vec![];
+        if self.interactive && self.jobs > 1 {
+            return Err(anyhow!("--interactive can only review one suite at a time; use --jobs 1"));
+        }
+
+        // Recordings are keyed by the flux version that produced them
+        let recording_dir = match &self.record {
+            Some(record_root) => {
+                let flux_version = run_cmd::flux_version().unwrap_or_else(|e| {
+                    status!("Warning: Could not determine flux version ({}); recording as 'unknown'", e);
+                    "unknown".to_string()
+                });
+                let recording_dir = flux_recording::version_dir(record_root, &flux_version);
+                flux_recording::init_recording_dir(&recording_dir, &flux_version)?;
+                status!("Recording flux output of {} to {:?}", flux_version, recording_dir);
+                Some(recording_dir)
+            }
+            None => None,
+        };
+        if let Some(replay_dir) = &self.replay {
+            if !replay_dir.is_dir() {
+                return Err(anyhow!("Replay directory {:?} does not exist", replay_dir));
+            }
+            match flux_recording::recorded_flux_version(replay_dir) {
+                Some(flux_version) => {
+                    status!("Replaying flux output of {} from {:?}", flux_version, replay_dir)
+                }
+                None => status!("Replaying flux output from {:?}", replay_dir),
+            }
+        }
+        // Results are collected per suite and sorted afterwards, since suites
+        // may finish in any order when running with several jobs.
+        let suite_evaluations: Mutex<Vec<(PathBuf, SuiteEvaluation)>> = Mutex::new(vec![]);
+        // Per-error failures that don't stop the suite's evaluation
+        let error_failures: Mutex<Vec<SuiteFailure>> = Mutex::new(vec![]);
+        // Set once the review is quit, so the remaining suites aren't reviewed
+        let stopped_reviewing = AtomicBool::new(false);
 
         // Define the action closure for evaluation
         let eval_action = |suite: &BenchmarkSuite,
                            worktree: &GitWorktreeDir| // Updated type
          -> Result<()> {
-            println!(
-                "Evaluating suite: {:?} in worktree {:?}",
-                suite.path(),
-                worktree.path(),
-            );
+            // Buffer this suite's output so parallel suites don't interleave
+            let mut log = String::new();
+            let mut failures = vec![];
+            let result = self.evaluate_suite(suite, worktree, recording_dir.as_deref(), &mut log, &mut failures);
+            if report_on_stdout {
+                eprint!("{}", log);
+            } else {
+                print!("{}", log);
+            }
+            error_failures.lock().unwrap().extend(failures);
+            let mut evaluations = result?;
+            let reviewed = std::mem::take(&mut evaluations.reviewed);
+            if !reviewed.is_empty() && !stopped_reviewing.load(Ordering::SeqCst) {
+                // `evaluate_suite` checked that the suite has its git info
+                let git_info = suite.git_info().context("git-info.json is missing")?;
+                if run_tui_review(&worktree.path().join(&git_info.subdir), reviewed)? {
+                    stopped_reviewing.store(true, Ordering::SeqCst);
+                }
+            }
+            suite_evaluations
+                .lock()
+                .unwrap()
+                .push((suite.path().to_path_buf(), evaluations));
+            Ok(())
+        };
 
-            // 1. Load benchmarks from the suite
-            let mut benchmarks_to_run = suite.load_benchmarks().with_context(|| {
-                format!("Failed to load benchmarks from suite: {:?}", suite.path())
-            })?;
+        let mut failures = process_benchmarks(
+            &bench_root,
+            &self.benchmarks,
+            &local_resolver,
+            cache_root, // Pass cache_root
+            ProcessOptions {
+                use_cache,
+                jobs: self.jobs,
+                keep_going: self.keep_going,
+            },
+            eval_action,
+        )?;
+        failures.extend(error_failures.into_inner().unwrap());
+        failures.sort_by(|a, b| a.suite_path.cmp(&b.suite_path));
+
+        let mut suite_evaluations = suite_evaluations.into_inner().unwrap();
+        suite_evaluations.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut evaluations: Vec<EvalRecord> = vec![];
+        let mut unmatched: Vec<UnmatchedError> = vec![];
+        for (_, suite_evaluation) in suite_evaluations {
+            evaluations.extend(suite_evaluation.records);
+            unmatched.extend(suite_evaluation.unmatched);
+        }
+
+        let results: Vec<ErrorEvalResult> =
+            evaluations.iter().map(|record| record.result.clone()).collect();
+        let summary_table = evaluator::generate_summary_table(&results, self.top_k);
+        status!("\nEvaluation Summary:");
+        status!("{}", summary_table);
+
+        // Errors that couldn't be evaluated aren't in the summary, so list them
+        // explicitly rather than silently shrinking the denominator.
+        let unmatched_report = evaluator::generate_unmatched_report(&unmatched);
+        if let Some(unmatched_report) = &unmatched_report {
+            status!("\n{}", unmatched_report);
+        }
+
+        if self.refinements {
+            status!("\nRefinement Comparison:");
+            status!("{}", evaluator::generate_refinement_table(&results));
+        }
 
-            // 2. Apply error name filters *within* the suite
-            if !self.benchmarks.errors.is_empty() {
-                benchmarks_to_run.retain(|b| self.benchmarks.errors.contains(&b.error_name));
-                 println!("  Applied error filter, {} benchmarks remain.", benchmarks_to_run.len());
+        let report = match self.output_format {
+            OutputFormat::Table => match unmatched_report {
+                Some(unmatched_report) => format!("{}\n\n{}", summary_table, unmatched_report),
+                None => summary_table,
+            },
+            OutputFormat::Json => evaluator::generate_json_report(&evaluations, &unmatched)?,
+            OutputFormat::Csv => evaluator::generate_csv_report(&evaluations, &unmatched),
+        };
+        match &self.output {
+            Some(output_path) => {
+                fs::write(output_path, report).with_context(|| {
+                    format!("Failed to write eval report to {:?}", output_path)
+                })?;
+                status!("Wrote {:?} eval report to {:?}", self.output_format, output_path);
             }
+            // The table was already printed above
+            None if self.output_format == OutputFormat::Table => {}
+            None => println!("{}", report),
+        }
 
-             if benchmarks_to_run.is_empty() {
-                 println!("  No benchmarks in this suite match the filters. Skipping evaluation.");
-                 return Ok(());
+        if let Some(baseline_name) = &self.save_baseline {
+            let baseline_path = baseline::save_baseline(&bench_root, baseline_name, &evaluations)?;
+            status!("Saved baseline '{}' to {:?}", baseline_name, baseline_path);
+        }
+
+        if !failures.is_empty() {
+            status!("\n{}", generate_failure_report(&failures));
+            // Without --keep-going, skipped suites and unparsable debug info
+            // are only reported, and don't fail the run
+            if self.keep_going {
+                return Err(anyhow!("{} failure(s) during evaluation", failures.len()));
             }
+        }
 
-            let benchmarks_map: HashMap<String, ErrorAndFixes> = benchmarks_to_run
-                                   .into_iter()
-                                   .map(|e| (e.error_name.clone(), e))
-                                   .collect();
+        Ok(())
+    }
+}
 
-            let git_info = suite.git_info().ok_or_else(|| {
-                anyhow!("Cannot run eval for suite {:?}: git-info.json is missing.", suite.path())
-            })?;
+impl EvalArgs {
+    /// Runs flux on a single suite (or replays its recorded output) and
+    /// evaluates every matching benchmark, writing progress messages to `log`.
+    /// The raw flux output is saved in `recording_dir` if given. Benchmarks
+    /// whose debug info can't be parsed are pushed to `failures` without
+    /// failing the whole suite.
+    fn evaluate_suite(
+        &self,
+        suite: &BenchmarkSuite,
+        worktree: &GitWorktreeDir,
+        recording_dir: Option<&Path>,
+        log: &mut String,
+        failures: &mut Vec<SuiteFailure>,
+    ) -> Result<SuiteEvaluation> {
+        let mut evaluation = SuiteEvaluation::default();
+        writeln!(
+            log,
+            "Evaluating suite: {:?} in worktree {:?}",
+            suite.path(),
+            worktree.path(),
+        )?;
+
+        // 1. Load benchmarks from the suite. All of them are kept (and matched
+        // against the flux output) to tell flux errors that have no benchmark
+        // from ones that were filtered out.
+        let benchmarks = suite
+            .load_benchmarks()
+            .with_context(|| format!("Failed to load benchmarks from suite: {:?}", suite.path()))
+            .context(FailureStage::LoadBenchmarks)?;
+
+        // 2. Apply benchmark filters *within* the suite
+        let is_selected = |benchmark: &ErrorAndFixes| self.benchmarks.matches_benchmark(benchmark);
+        let num_selected = benchmarks.iter().filter(|b| is_selected(b)).count();
+        if self.benchmarks.filters_benchmarks() {
+            writeln!(log, "  Applied benchmark filters, {} benchmarks remain.", num_selected)?;
+        }
+
+        if num_selected == 0 {
+            writeln!(log, "  No benchmarks in this suite match the filters. Skipping evaluation.")?;
+            return Ok(evaluation);
+        }
+
+        let git_info = suite.git_info().ok_or_else(|| {
+            anyhow!("Cannot run eval for suite {:?}: git-info.json is missing.", suite.path())
+        })?;
+
+        // The evaluation context is the relevant subdirectory *within* the worktree
+        let eval_subdir = worktree.path().join(&git_info.subdir);
+
+        let flux_output = match &self.replay {
+            Some(replay_dir) => {
+                writeln!(log, "  Replaying recorded flux output from {:?}", replay_dir)?;
+                flux_recording::load_recording(replay_dir, git_info).context(FailureStage::FluxRun)?
+            }
+            None => {
+                writeln!(log, "  Running flux in evaluation subdir: {:?}", eval_subdir)?;
+                // Enable debug info for evaluation to get blame spans
+                let flux_output =
+                    run_cmd::run_flux_raw(&eval_subdir, true).context(FailureStage::FluxRun)?;
+                if let Some(recording_dir) = recording_dir {
+                    let recording_path =
+                        flux_recording::save_recording(recording_dir, git_info, &flux_output)?;
+                    writeln!(log, "  Recorded flux output to {:?}", recording_path)?;
+                }
+                flux_output
+            }
+        };
+        let flux_errors = run_cmd::parse_flux_output(&eval_subdir, &git_info.commit, &flux_output)
+            .context(FailureStage::FluxRun)?;
+        writeln!(log, "  Found {} errors from flux run.", flux_errors.len())?;
 
-            // The evaluation context is the relevant subdirectory *within* the worktree
-            let eval_subdir = worktree.path().join(&git_info.subdir);
-
-
-            println!("  Running flux in evaluation subdir: {:?}", eval_subdir);
-            // Enable debug info for evaluation to get blame spans
-            let flux_errors = run_cmd::run_flux_in_dir(&eval_subdir, &git_info.commit, true)?;
-            println!("  Found {} errors from flux run.", flux_errors.len());
-
-            for error in flux_errors {
-                //println!("Checking flux error: {}", error.error_name); // Verbose
-                if let Some(benchmark_error) = benchmarks_map.get(&error.error_name) {
-                    println!("    Evaluating benchmark match: {}", benchmark_error.error_name);
-                    match evaluator::extract_constraint_debug_info(&error.error) {
-                        Ok(Some(constraint_debug_info)) => {
-                            let eval = evaluator::evaluate_error(&constraint_debug_info, benchmark_error);
-                            println!("      Evaluation result: {:?}", eval);
-                            evaluations.push(eval);
-                        },
-                        Ok(None) => {
-                             eprintln!("      Warning: Could not extract constraint debug info for {}", error.error_name);
+        let path_resolver = SpanPathResolver::new(worktree.path(), &git_info.subdir);
+        let matching = if self.strict_columns {
+            SpanMatching::Columns
+        } else {
+            SpanMatching::Lines
+        };
+        let unmatched_error = |error_name: &str, reason| UnmatchedError {
+            repo_name: git_info.repo_name.clone(),
+            subdir: git_info.subdir.clone(),
+            commit: git_info.commit.clone(),
+            error_name: error_name.to_string(),
+            reason,
+        };
+        // Match by fingerprint first, so errors that only moved (or were
+        // renumbered) are still evaluated against their benchmark
+        let benchmark_matches = match_errors(&flux_errors, &benchmarks);
+        let mut emitted_benchmarks = vec![false; benchmarks.len()];
+        for (error, benchmark_idx) in flux_errors.iter().zip(benchmark_matches) {
+            let Some(benchmark_idx) = benchmark_idx else {
+                // Errors without a benchmark are filtered like an unannotated one
+                if is_selected(error) {
+                    writeln!(log, "    Warning: Flux error {} has no benchmark", error.error_name)?;
+                    evaluation
+                        .unmatched
+                        .push(unmatched_error(&error.error_name, UnmatchedReason::NoBenchmark));
+                }
+                continue;
+            };
+            emitted_benchmarks[benchmark_idx] = true;
+            let benchmark_error = &benchmarks[benchmark_idx];
+            if is_selected(benchmark_error) {
+                if benchmark_error.error_name != error.error_name {
+                    writeln!(
+                        log,
+                        "    Matched flux error {} to benchmark {} by fingerprint",
+                        error.error_name, benchmark_error.error_name
+                    )?;
+                }
+                writeln!(log, "    Evaluating benchmark match: {}", benchmark_error.error_name)?;
+                match evaluator::extract_constraint_debug_info(&error.error) {
+                    Ok(Some(mut constraint_debug_info)) => {
+                        if constraint_debug_info.num_notes() > 1 {
+                            writeln!(
+                                log,
+                                "      Found {} constraint debug info notes; combining their blame spans.",
+                                constraint_debug_info.num_notes()
+                            )?;
                         }
-                        Err(e) => {
-                             eprintln!("      Warning: Error parsing constraint debug info for {}: {}", error.error_name, e);
+                        for file in path_resolver.resolve_spans(&mut constraint_debug_info) {
+                            writeln!(
+                                log,
+                                "      Warning: Blame span file {:?} of {} could not be mapped into the worktree",
+                                file, error.error_name
+                            )?;
                         }
+                        let eval = evaluator::evaluate_error(&constraint_debug_info, benchmark_error, matching);
+                        writeln!(log, "      Evaluation result: {:?}", eval)?;
+                        if self.interactive {
+                            evaluation.reviewed.push(ReviewedError {
+                                benchmark: benchmark_error.clone(),
+                                constraint_info: constraint_debug_info,
+                                result: eval.clone(),
+                            });
+                        }
+                        evaluation.records.push(EvalRecord {
+                            repo_name: git_info.repo_name.clone(),
+                            subdir: git_info.subdir.clone(),
+                            commit: git_info.commit.clone(),
+                            fingerprint: benchmark_error.fingerprint(),
+                            result: eval,
+                        });
+                    }
+                    Ok(None) => {
+                        writeln!(log, "      Warning: Could not extract constraint debug info for {}", error.error_name)?;
+                        evaluation
+                            .unmatched
+                            .push(unmatched_error(&benchmark_error.error_name, UnmatchedReason::NoDebugInfo));
+                    }
+                    Err(e) => {
+                        writeln!(log, "      Warning: Error parsing constraint debug info for {}: {}", error.error_name, e)?;
+                        evaluation
+                            .unmatched
+                            .push(unmatched_error(&benchmark_error.error_name, UnmatchedReason::DebugInfoParseError));
+                        let mut failure = SuiteFailure::new(suite.path(), FailureStage::DebugInfoParse, e);
+                        failure.error_name = Some(benchmark_error.error_name.clone());
+                        failures.push(failure);
                     }
-                } else {
-                    //println!("    Flux error {} not found in benchmark suite.", error.error_name); // Verbose
                 }
             }
+        }
 
+        let mut not_emitted: Vec<&String> = benchmarks
+            .iter()
+            .zip(&emitted_benchmarks)
+            .filter(|(benchmark, emitted)| !**emitted && is_selected(benchmark))
+            .map(|(benchmark, _)| &benchmark.error_name)
+            .collect();
+        not_emitted.sort();
+        for error_name in not_emitted {
+            writeln!(log, "    Warning: Benchmark {} was not emitted by flux", error_name)?;
+            evaluation
+                .unmatched
+                .push(unmatched_error(error_name, UnmatchedReason::NotEmitted));
+        }
 
-            // 4. Output results as needed (summary printed after loop)
-            println!("  Finished evaluation for suite: {:?}", suite.path());
+        writeln!(log, "  Finished evaluation for suite: {:?}", suite.path())?;
+        Ok(evaluation)
+    }
+}
 
-            Ok(())
+/// The outcome of evaluating a single suite.
+#[derive(Default)]
+struct SuiteEvaluation {
+    records: Vec<EvalRecord>,
+    /// Benchmarks and flux errors that couldn't be evaluated
+    unmatched: Vec<UnmatchedError>,
+    /// The evaluated benchmarks, to review with `--interactive`
+    reviewed: Vec<ReviewedError>,
+}
+
+/// An evaluated benchmark with what flux blamed for it.
+struct ReviewedError {
+    benchmark: ErrorAndFixes,
+    /// With its spans resolved against the run dir
+    constraint_info: ConstraintDebugInfoDeserialize,
+    result: ErrorEvalResult,
+}
+
+#[derive(Args, Clone)]
+struct CompareArgs {
+    /// The old run: a baseline name or the path to a JSON eval report
+    base: String,
+    /// The new run: a baseline name or the path to a JSON eval report
+    new: String,
+    /// Also fail if errors of the old run are missing from the new one (and
+    /// so can no longer be evaluated).
+    #[arg(long)]
+    fail_on_missing: bool,
+}
+
+impl CompareArgs {
+    fn run(&self, bench_root: &Path) -> Result<()> {
+        let old_records = baseline::load_run(bench_root, &self.base)?;
+        let new_records = baseline::load_run(bench_root, &self.new)?;
+        println!(
+            "Comparing {} ({} errors) against {} ({} errors)",
+            self.new,
+            new_records.len(),
+            self.base,
+            old_records.len()
+        );
+
+        let comparison = baseline::compare_runs(&old_records, &new_records);
+        println!("{}", baseline::generate_comparison_report(&comparison));
+
+        let num_regressions = comparison.num_regressions();
+        if num_regressions > 0 {
+            return Err(anyhow!(
+                "Found {} regression(s) relative to {}",
+                num_regressions,
+                self.base
+            ));
+        }
+        if self.fail_on_missing && !comparison.removed.is_empty() {
+            return Err(anyhow!(
+                "Found {} error(s) of {} missing from {}",
+                comparison.removed.len(),
+                self.base,
+                self.new
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Format of the listing produced by `list`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ListFormat {
+    /// Human-readable table with totals
+    #[default]
+    Table,
+    /// A JSON object with every suite and error, and the totals
+    Json,
+}
+
+#[derive(Args, Clone)]
+struct ListArgs {
+    #[command(flatten)]
+    benchmarks: BenchmarkArgs,
+    /// Format of the listing.
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    format: ListFormat,
+    /// Write the listing to this file instead of stdout.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+impl ListArgs {
+    fn run(&self, bench_root: &Path) -> Result<()> {
+        // Only the benchmark files are read, so no worktrees are needed
+        let suites = discover_suites(bench_root, &self.benchmarks)?;
+        let listings: Vec<inventory::SuiteListing> = suites
+            .iter()
+            .map(|suite| inventory::SuiteListing::new(suite, &self.benchmarks))
+            // Hide suites the benchmark filters emptied, but not broken ones
+            .filter(|listing| {
+                !self.benchmarks.filters_benchmarks()
+                    || !listing.errors.is_empty()
+                    || listing.load_error.is_some()
+            })
+            .collect();
+
+        let report = match self.format {
+            ListFormat::Table => inventory::generate_list_table(&listings),
+            ListFormat::Json => inventory::generate_json_list(&listings)?,
         };
+        match &self.output {
+            Some(output_path) => {
+                fs::write(output_path, report).with_context(|| {
+                    format!("Failed to write benchmark list to {:?}", output_path)
+                })?;
+                println!("Wrote {:?} benchmark list to {:?}", self.format, output_path);
+            }
+            None => println!("{}", report),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Args, Clone)]
+struct ValidateArgs {
+    #[command(flatten)]
+    benchmarks: BenchmarkArgs,
+    /// Use a temporary worktree instead of the default persistent cached one (saves disk space).
+    #[arg(long, default_value_t = false)] // Default is cached for 'validate'
+    no_cache: bool,
+}
+
+impl ValidateArgs {
+    fn run(
+        &self,
+        local_resolver: LocalPathResolver,
+        bench_root: PathBuf,
+        cache_root: &Path,
+    ) -> Result<()> {
+        println!("Running Validate command...");
 
-        process_benchmarks(
+        // Checks that don't need the source (these also cover suites whose
+        // worktree can't be checked out)
+        let mut issues = vec![];
+        for suite in discover_suites(&bench_root, &self.benchmarks)? {
+            issues.extend(validate::validate_suite_files(&suite)?);
+        }
+
+        let source_issues = Mutex::new(vec![]);
+        let validate_action = |suite: &BenchmarkSuite, worktree: &GitWorktreeDir| -> Result<()> {
+            let git_info = suite.git_info().ok_or_else(|| {
+                anyhow!("Cannot validate suite {:?}: git-info.json is missing.", suite.path())
+            })?;
+            let run_dir = worktree.path().join(&git_info.subdir);
+            println!("Validating suite {:?} against {:?}", suite.path(), run_dir);
+            source_issues
+                .lock()
+                .unwrap()
+                .extend(validate::validate_suite_source(suite, &run_dir)?);
+            Ok(())
+        };
+        let failures = process_benchmarks(
             &bench_root,
             &self.benchmarks,
             &local_resolver,
-            cache_root, // Pass cache_root
-            use_cache,  // Pass cache preference
-            eval_action,
+            cache_root,
+            ProcessOptions {
+                use_cache: !self.no_cache,
+                jobs: 1,
+                keep_going: true,
+            },
+            validate_action,
         )?;
+        issues.extend(source_issues.into_inner().unwrap());
+        // Missing git-info.json is already an issue
+        let failures: Vec<SuiteFailure> = failures
+            .into_iter()
+            .filter(|failure| failure.stage != FailureStage::GitInfo)
+            .collect();
 
-        println!("\nEvaluation Summary:");
-        println!("{}", evaluator::generate_summary_table(&evaluations));
+        println!("\n{}", validate::generate_validation_report(&issues));
+        if !failures.is_empty() {
+            println!("\n{}", generate_failure_report(&failures));
+        }
+        if !issues.is_empty() || !failures.is_empty() {
+            return Err(anyhow!(
+                "Found {} issue(s) and {} suite failure(s)",
+                issues.len(),
+                failures.len()
+            ));
+        }
+        println!("All benchmarks are valid.");
+        Ok(())
+    }
+}
+
+#[derive(Args, Clone)]
+struct PortArgs {
+    /// The suites to port (all from the same repo)
+    #[command(flatten)]
+    benchmarks: BenchmarkArgs,
+    /// The (full) hash of the newer commit to port the benchmarks to
+    #[arg(long)]
+    to: String,
+    /// Overwrite benchmarks that already exist at the new commit. By default
+    /// they are skipped.
+    #[arg(short = 'f', long)]
+    overwrite_existing: bool,
+    /// Use a persistent cached worktree instead of a temporary one (useful for build caching during editing).
+    #[arg(long, default_value_t = false)] // Default is temporary for 'port'
+    cache: bool,
+}
 
+impl PortArgs {
+    fn run(
+        &self,
+        local_resolver: LocalPathResolver,
+        bench_root: PathBuf,
+        cache_root: &Path,
+    ) -> Result<()> {
+        println!("Running Port command...");
+        let suites = discover_suites(&bench_root, &self.benchmarks)?;
+        if suites.is_empty() {
+            println!("No suites match the filters. Nothing to port.");
+            return Ok(());
+        }
+        if suites.iter().any(|suite| suite.repo_name() != suites[0].repo_name()) {
+            return Err(anyhow!(
+                "The selected suites span several repos; select the suites of a single repo with --repos"
+            ));
+        }
+
+        let cache_root = cache_root.canonicalize()?;
+        let cache_repo = CachedRepository::new(cache_root, &local_resolver);
+        for suite in &suites {
+            if suite.commit_hash() == self.to {
+                println!("Suite {:?} is already at {}. Skipping.", suite.path(), self.to);
+                continue;
+            }
+            self.port_suite(&bench_root, &cache_repo, suite)
+                .with_context(|| format!("Failed to port suite {:?}", suite.path()))?;
+        }
+        Ok(())
+    }
+
+    /// Ports the benchmarks of `suite` to the new commit, saving the ones that
+    /// were carried over cleanly and opening the TUI on the rest.
+    fn port_suite(
+        &self,
+        bench_root: &Path,
+        cache_repo: &CachedRepository,
+        suite: &BenchmarkSuite,
+    ) -> Result<()> {
+        println!("Porting suite: {:?}", suite.path());
+        let git_info = suite.git_info().ok_or_else(|| {
+            anyhow!("Cannot port suite {:?}: git-info.json is missing.", suite.path())
+        })?;
+
+        let mut old_benchmarks = suite.load_benchmarks().with_context(|| {
+            format!("Failed to load benchmarks from suite: {:?}", suite.path())
+        })?;
+        if self.benchmarks.filters_benchmarks() {
+            old_benchmarks.retain(|b| self.benchmarks.matches_benchmark(b));
+            println!("  Applied benchmark filters, {} benchmarks remain.", old_benchmarks.len());
+        }
+        if old_benchmarks.is_empty() {
+            println!("  No benchmarks in this suite match the filters. Skipping.");
+            return Ok(());
+        }
+
+        println!("Resolving code for {}@{}...", suite.repo_name(), &self.to[..7.min(self.to.len())]);
+        let worktree = cache_repo.get_worktree(suite.repo_name(), &self.to, &git_info.remote, self.cache)?;
+        println!("Code worktree ready at: {:?}", worktree.path());
+
+        // The old commit has to be in the same repository as the new one
+        let repo = git2::Repository::open(&worktree.repo_path)
+            .with_context(|| format!("Failed to open repository at {:?}", worktree.repo_path))?;
+        let remapper = port::LineRemapper::new(&repo, suite.commit_hash(), &self.to)?;
+
+        let new_git_info = GitInformation {
+            commit: self.to.clone(),
+            ..git_info.clone()
+        };
+        let run_dir = worktree.path().join(&git_info.subdir);
+        println!("  Running flux in: {:?}", run_dir);
+        let new_errors = run_cmd::run_flux_in_dir(&run_dir, &self.to, false)?;
+        println!("  Found {} errors from flux run.", new_errors.len());
+
+        let result = port::port_benchmarks(&old_benchmarks, new_errors, &remapper, &git_info.subdir);
+
+        let mut new_suite = BenchmarkSuite::new(
+            bench_root,
+            &new_git_info.repo_name,
+            &new_git_info.subdir,
+            &new_git_info.commit,
+        )?;
+        println!("Target benchmark suite path: {:?}", new_suite.path());
+        new_suite.lock()?;
+        let existing_names: Vec<String> = new_suite
+            .load_benchmarks()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|benchmark| benchmark.error_name)
+            .collect();
+
+        let mut ported = vec![];
+        let mut to_edit = vec![];
+        for ported_benchmark in result.ported {
+            let error_name = &ported_benchmark.benchmark.error_name;
+            if !self.overwrite_existing && existing_names.contains(error_name) {
+                println!(
+                    "Skipping existing benchmark (use --overwrite-existing to replace it): {}",
+                    error_name
+                );
+                continue;
+            }
+            if ported_benchmark.is_clean() {
+                println!("Ported {} to {}.", ported_benchmark.old_error_name, error_name);
+            } else {
+                println!(
+                    "Ported {} to {}, but dropped {} changed fix line(s):",
+                    ported_benchmark.old_error_name,
+                    error_name,
+                    ported_benchmark.dropped_lines.len()
+                );
+                for line in &ported_benchmark.dropped_lines {
+                    println!("    {}:{}", line.file.display(), line.line);
+                }
+                to_edit.push(ported_benchmark.benchmark.clone());
+            }
+            ported.push(ported_benchmark.benchmark);
+        }
+        for error_name in &result.not_reproduced {
+            println!("Warning: Benchmark {} is not reported by flux at the new commit", error_name);
+        }
+        if !result.new_errors.is_empty() {
+            println!(
+                "Flux reports {} error(s) at the new commit with no benchmark to port (use `add` to annotate them):",
+                result.new_errors.len()
+            );
+            for error_name in &result.new_errors {
+                println!("    {}", error_name);
+            }
+        }
+
+        // Save everything first so that skipping an error in the TUI keeps
+        // whatever could be carried over
+        new_suite.write_benchmarks(&ported, &new_git_info)?;
+
+        if !to_edit.is_empty() {
+            println!("Launching TUI editor for {} benchmarks that need review...", to_edit.len());
+            run_tui_editor(&run_dir, &new_git_info, new_suite, to_edit, HashMap::new())?;
+        }
         Ok(())
     }
 }
@@ -437,20 +1180,226 @@ fn main() -> Result<()> {
     cli.command.run(cli.bench_root, cli.cache_root)
 }
 
+/// What an editing session did to a benchmark.
+enum BenchmarkEdit {
+    /// Not saved in the session (skipped, or not reached before quitting)
+    Unchanged,
+    /// Saved with these fixes
+    Edited(Vec<Fix>),
+    /// Deleted from the suite
+    Deleted,
+}
+
+/// How an error was left in an editing session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionOutcome {
+    Unvisited,
+    /// Left by jumping to another error
+    Visited,
+    Saved,
+    Skipped,
+    Deleted,
+    /// The session was quit while on this error, abandoning its changes since
+    /// its last save
+    Quit,
+}
+
+/// Lists the errors of the session with their current status, for the
+/// navigator.
+fn session_errors(
+    errors_and_fixes: &[ErrorAndFixes],
+    app_states: &[Option<AppState>],
+    outcomes: &[SessionOutcome],
+) -> Vec<SessionError> {
+    errors_and_fixes
+        .iter()
+        .zip(app_states)
+        .zip(outcomes)
+        .map(|((error_and_fixes, app_state), outcome)| {
+            let status = match (outcome, app_state) {
+                (SessionOutcome::Deleted, _) => ErrorStatus::Deleted,
+                (SessionOutcome::Skipped, _) => ErrorStatus::Skipped,
+                (_, Some(app_state)) => app_state.status(),
+                (_, None) => ErrorStatus::of_fixes(&error_and_fixes.fixes),
+            };
+            SessionError {
+                error_name: error_and_fixes.error_name.clone(),
+                status,
+                is_modified: *outcome != SessionOutcome::Saved
+                    && app_state.as_ref().is_some_and(|app_state| app_state.is_modified()),
+            }
+        })
+        .collect()
+}
+
+/// Shows the eval results of `reviewed` in the TUI, one error after the other
+/// (or in any order through the navigator). Nothing is saved.
+///
+/// # Arguments
+/// * `dir_path`: Path where flux ran, which the benchmarks are relative to (absolute).
+/// * `reviewed`: The evaluated benchmarks of a suite.
+///
+/// # Returns
+/// * Whether the review was quit, so no further suites should be reviewed.
+fn run_tui_review(dir_path: &Path, reviewed: Vec<ReviewedError>) -> Result<bool> {
+    println!("Reviewing {} evaluated error(s) in context: {:?}", reviewed.len(), dir_path);
+    let num_errors = reviewed.len();
+    let benchmarks: Vec<ErrorAndFixes> = reviewed.iter().map(|review| review.benchmark.clone()).collect();
+    let mut reviewed: Vec<Option<ReviewedError>> = reviewed.into_iter().map(Some).collect();
+    let mut app_states: Vec<Option<AppState>> = (0..num_errors).map(|_| None).collect();
+    let outcomes = vec![SessionOutcome::Unvisited; num_errors];
+
+    stdout().execute(EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let mut current = Some(0);
+    let mut quit = false;
+    let result = (|| -> Result<()> {
+        while let Some(idx) = current {
+            let session_errors = session_errors(&benchmarks, &app_states, &outcomes);
+            let app_state = match &mut app_states[idx] {
+                Some(app_state) => app_state,
+                slot => {
+                    let review = reviewed[idx].take().context("Error was already reviewed")?;
+                    let mut app_state = AppState::new(&review.benchmark, dir_path)
+                        .context("Failed to initialize TUI state")?;
+                    app_state.set_constraint_info(review.constraint_info, dir_path);
+                    app_state.set_eval_result(&review.result, dir_path)?;
+                    slot.insert(app_state)
+                }
+            };
+            app_state.enter_session(session_errors, idx);
+
+            terminal.clear()?;
+            run_app(&mut terminal, app_state).context("TUI application error")?;
+
+            let next = (idx + 1 < num_errors).then_some(idx + 1);
+            current = match app_state.exit_intent {
+                Some(ExitIntent::Quit) | None => {
+                    quit = true;
+                    None
+                }
+                Some(ExitIntent::JumpTo(target)) => Some(target),
+                // The annotation can't be changed, so anything else moves on
+                Some(ExitIntent::Skip | ExitIntent::SaveAndNext | ExitIntent::Delete) => next,
+            };
+        }
+        Ok(())
+    })();
+
+    // Restore terminal state regardless of how TUI exited
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    println!("Review finished.");
+    result.map(|()| quit)
+}
+
+/// The changes an editing session makes to a suite.
+#[derive(Default)]
+struct SuiteChanges {
+    /// Benchmarks without a file in the suite yet
+    added: Vec<ErrorAndFixes>,
+    /// Benchmarks whose fixes changed
+    modified: Vec<ErrorAndFixes>,
+    /// Names of the benchmarks to remove from the suite
+    deleted: Vec<String>,
+    num_unchanged: usize,
+    /// New errors that were left without being saved, so aren't added
+    num_not_added: usize,
+}
+
+impl SuiteChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+
+    /// Describes the changes, one benchmark per line.
+    fn preview(&self, suite: &BenchmarkSuite) -> String {
+        let mut preview = format!("Changes to suite {:?}:", suite.path());
+        for benchmark in &self.added {
+            write!(preview, "\n  + add    {} ({} fixes)", benchmark.error_name, benchmark.fixes.len()).unwrap();
+        }
+        for benchmark in &self.modified {
+            write!(preview, "\n  ~ modify {} ({} fixes)", benchmark.error_name, benchmark.fixes.len()).unwrap();
+        }
+        for error_name in &self.deleted {
+            write!(preview, "\n  - delete {}", error_name).unwrap();
+        }
+        write!(preview, "\n  {} benchmark(s) unchanged", self.num_unchanged).unwrap();
+        if self.num_not_added > 0 {
+            write!(preview, "\n  {} new error(s) not added", self.num_not_added).unwrap();
+        }
+        preview
+    }
+}
+
+/// Asks a yes/no question on stdin, defaulting to no.
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::Write::flush(&mut stdout())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Takes the constraint debug info notes out of `errors` and returns them by
+/// error name, with their spans resolved against the run dir. Errors whose
+/// notes can't be parsed get no debug info.
+fn take_constraint_infos(
+    errors: &mut [ErrorAndFixes],
+    path_resolver: &SpanPathResolver,
+) -> HashMap<String, ConstraintDebugInfoDeserialize> {
+    let mut constraint_infos = HashMap::new();
+    for error in errors {
+        match evaluator::extract_constraint_debug_info(&error.error) {
+            Ok(Some(mut constraint_info)) => {
+                for file in path_resolver.resolve_spans(&mut constraint_info) {
+                    println!(
+                        "  Warning: Blame span file {:?} of {} could not be mapped into the worktree",
+                        file, error.error_name
+                    );
+                }
+                constraint_infos.insert(error.error_name.clone(), constraint_info);
+            }
+            Ok(None) => println!("  Warning: No constraint debug info for {}", error.error_name),
+            Err(e) => println!(
+                "  Warning: Error parsing constraint debug info for {}: {}",
+                error.error_name, e
+            ),
+        }
+        evaluator::strip_constraint_debug_info(&mut error.error);
+    }
+    constraint_infos
+}
+
 /// Runs the TUI editor on each of the errors_and_fixes, collecting an
-/// annotation for them. The save it performs overwrites any existing
-/// ErrorAndFix files for the *processed* errors, potentially preserving others.
+/// annotation for them. Errors are visited in order, but the navigator can
+/// jump between them freely; changes are kept per error until the end of the
+/// session. Afterwards, the resulting changes to the suite are
+/// previewed and, once confirmed, saved:
+/// - saved benchmarks (and ones left with changes by jumping away) are
+///   written with the fixes from the session,
+/// - skipped and unvisited benchmarks, and the one the session was quit on,
+///   keep their existing fixes (and aren't added if they have no file yet),
+///   unless saved earlier in the session, in which case the one quit on keeps
+///   the fixes of its last save,
+/// - deleted benchmarks have their files removed.
+///
+/// Benchmarks of the suite that weren't passed in are left alone.
 ///
 /// # Arguments
 /// * `dir_path`: Path where flux command was run / TUI context base (absolute).
 /// * `git_info`: Git info (can be from discover or from suite).
 /// * `suite`: BenchmarkSuite instance (mutable to allow updating git_info/saving).
 /// * `errors_and_fixes`: The benchmarks to process in the TUI.
+/// * `constraint_infos`: Flux's debug info to overlay on the errors, by error name.
 fn run_tui_editor(
     dir_path: &Path,
     git_info: &GitInformation,
     mut suite: BenchmarkSuite,
     errors_and_fixes_to_process: Vec<ErrorAndFixes>,
+    mut constraint_infos: HashMap<String, ConstraintDebugInfoDeserialize>,
 ) -> Result<()> {
      if errors_and_fixes_to_process.is_empty() {
         println!("TUI: No specific errors provided to process.");
@@ -466,157 +1415,169 @@ fn run_tui_editor(
         errors_and_fixes_to_process.len(),
         dir_path
     );
-
-    // Stores the results from the TUI session(s)
-    let mut final_benchmarks_map: HashMap<String, ErrorAndFixes> = HashMap::new();
+    // Fail before any annotation work if another session is editing the suite
+    suite.lock()?;
+
+    // The editor state of every error visited so far, so changes survive
+    // jumping between errors
+    let num_errors = errors_and_fixes_to_process.len();
+    let mut app_states: Vec<Option<AppState>> = (0..num_errors).map(|_| None).collect();
+    let mut outcomes = vec![SessionOutcome::Unvisited; num_errors];
+    // The fixes as of each error's last save, kept if the session is later
+    // quit on it
+    let mut saved_fixes: Vec<Option<Vec<Fix>>> = vec![None; num_errors];
 
     stdout().execute(EnterAlternateScreen)?;
     enable_raw_mode()?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
-    let mut exit_tui = false;
-    for initial_error_and_fixes in errors_and_fixes_to_process.into_iter() {
-        if exit_tui {
-            break;
-        }
-        // Process each 'fix' entry within the initial benchmark as a separate TUI session
-        // If there are no fixes, create a dummy entry to run the TUI once for annotation.
-        let fixes_to_process = if initial_error_and_fixes.fixes.is_empty() {
-             vec![None] // Represent "no existing fix" with None
-        } else {
-             initial_error_and_fixes.fixes.iter().map(Some).collect::<Vec<_>>()
-        };
-
+    let mut current = Some(0);
+    while let Some(idx) = current {
+        let initial_error_and_fixes = &errors_and_fixes_to_process[idx];
         println!("  Processing error: {}", initial_error_and_fixes.error_name); // Log which error we are entering
 
-        for existing_fix_opt in fixes_to_process {
-             if exit_tui { break; }
-
-             // Create a temporary ErrorAndFixes with only the current fix (or none)
-             // for the AppState initialization. Use the error details from the outer loop.
-             let mut current_eaf_for_tui = initial_error_and_fixes.clone();
-             current_eaf_for_tui.fixes = existing_fix_opt.cloned().map_or(vec![], |f| vec![f]);
-
-             // println!("    TUI SubLoop: Editing fix (exists: {})", existing_fix_opt.is_some());
-
-            loop { // Inner loop for SaveAndRedo
-                 let mut app_state = AppState::new(&current_eaf_for_tui, dir_path)
-                     .context("Failed to initialize TUI state")?;
+        let session_errors = session_errors(&errors_and_fixes_to_process, &app_states, &outcomes);
+        let app_state = match &mut app_states[idx] {
+            Some(app_state) => app_state,
+            // All of the error's fixes are edited in a single session
+            slot => {
+                let mut app_state = AppState::new(initial_error_and_fixes, dir_path)
+                    .context("Failed to initialize TUI state")?;
+                if let Some(constraint_info) = constraint_infos.remove(&initial_error_and_fixes.error_name) {
+                    app_state.set_constraint_info(constraint_info, dir_path);
+                }
+                slot.insert(app_state)
+            }
+        };
+        app_state.enter_session(session_errors, idx);
+
+        terminal.clear()?;
+        // Terminal interaction loop
+        if let Err(e) = run_app(&mut terminal, app_state) {
+            // Clean up terminal before propagating error
+            disable_raw_mode()?;
+            stdout().execute(LeaveAlternateScreen)?;
+            return Err(e).context("TUI application error");
+        }
 
-                terminal.clear()?;
-                // Terminal interaction loop
-                 if let Err(e) = run_app(&mut terminal, &mut app_state) {
-                     // Clean up terminal before propagating error
-                     disable_raw_mode()?;
-                     stdout().execute(LeaveAlternateScreen)?;
-                     return Err(e).context("TUI application error");
-                 }
+        // Process TUI exit intent
+        let next = (idx + 1 < num_errors).then_some(idx + 1);
+        current = match app_state.exit_intent {
+            Some(ExitIntent::Quit) | None => { // Treat None as Quit here
+                println!("    TUI intent: Quit");
+                outcomes[idx] = SessionOutcome::Quit;
+                None
+            }
+            Some(ExitIntent::Skip) => {
+                println!("    TUI intent: Skip");
+                outcomes[idx] = SessionOutcome::Skipped;
+                next
+            }
+            Some(ExitIntent::Delete) => {
+                println!("    TUI intent: Delete");
+                outcomes[idx] = SessionOutcome::Deleted;
+                next
+            }
+            Some(ExitIntent::SaveAndNext) => {
+                println!("    TUI intent: SaveAndNext");
+                outcomes[idx] = SessionOutcome::Saved;
+                match app_state.fixes(dir_path) {
+                    Ok(fixes) => saved_fixes[idx] = Some(fixes),
+                    Err(e) => eprintln!("      Error collecting fixes from TUI state: {}", e),
+                }
+                next
+            }
+            Some(ExitIntent::JumpTo(target)) => {
+                println!("    TUI intent: JumpTo {}", target);
+                // Revisiting a skipped error reconsiders it
+                if matches!(outcomes[idx], SessionOutcome::Unvisited | SessionOutcome::Skipped) {
+                    outcomes[idx] = SessionOutcome::Visited;
+                }
+                Some(target)
+            }
+        };
+    }
 
-                // Process TUI exit intent
-                match app_state.exit_intent {
-                     Some(ExitIntent::Quit) | None => { // Treat None as Quit here
-                         println!("    TUI intent: Quit");
-                         exit_tui = true;
-                         break; // Exit inner loop (SaveAndRedo)
-                     }
-                     Some(ExitIntent::Skip) => {
-                         println!("    TUI intent: Skip");
-                         // Don't save anything for this fix/error, just break inner loop
-                         // Clear any potential fixes added in previous redo loops for this error name
-                         // We should perhaps only remove the *specific* fix being edited if skipping?
-                         // For simplicity, let's just not add it to final_benchmarks_map
-                         break; // Exit inner loop (SaveAndRedo), proceed to next fix/error
-                     }
-                     Some(ExitIntent::SaveAndNext) | Some(ExitIntent::SaveAndRedo) => {
-                         println!("    TUI intent: SaveAndNext or SaveAndRedo");
-                         match app_state.fixes(dir_path) {
-                             Ok(collected_fix) => {
-                                // Check if the fix is actually non-empty before saving
-                                if !collected_fix.fix_lines.is_empty() || collected_fix.note.is_some() {
-                                     // Aggregate the collected fix into the final map
-                                     final_benchmarks_map
-                                         .entry(initial_error_and_fixes.error_name.clone())
-                                         .or_insert_with(|| {
-                                             // Create a new entry if this is the first valid fix for this error
-                                             let mut e = initial_error_and_fixes.clone();
-                                             e.fixes = Vec::new(); // Start with empty fixes
-                                             e
-                                         })
-                                         .fixes // Modify the fixes vector
-                                         .push(collected_fix);
-                                     println!("      Collected fix: {} lines, note: {}", final_benchmarks_map.get(&initial_error_and_fixes.error_name).unwrap().fixes.last().unwrap().fix_lines.len(), final_benchmarks_map.get(&initial_error_and_fixes.error_name).unwrap().fixes.last().unwrap().note.is_some());
-                                } else {
-                                     println!("      Skipping save for empty fix/note.");
-                                     // If an empty fix was generated, ensure the error name doesn't persist
-                                     // with empty fixes if it was meant to be effectively deleted/skipped.
-                                     // Check if any previous fix for this error existed in the map
-                                     if let Some(entry) = final_benchmarks_map.get_mut(&initial_error_and_fixes.error_name) {
-                                         if entry.fixes.is_empty() {
-                                             // If the only fix(es) resulted in empty, remove the whole entry?
-                                             // Or just don't add this empty one. Let's not add.
-                                         }
-                                     }
-                                }
-                             }
-                             Err(e) => {
-                                 eprintln!("      Error collecting fixes from TUI state: {}", e);
-                                 // If collecting fixes fails, should we retry or skip?
-                                 // Let's skip saving this attempt and proceed based on intent.
-                                 if matches!(app_state.exit_intent, Some(ExitIntent::SaveAndNext)) {
-                                     break; // Skip saving, move to next
-                                 } else {
-                                     // SaveAndRedo - maybe loop again? Or just break? Let's break.
-                                      eprintln!("      Proceeding without saving due to error.");
-                                      break; // Exit inner loop
-                                 }
-                             }
-                         };
-
-                        if matches!(app_state.exit_intent, Some(ExitIntent::SaveAndNext)) {
-                             println!("    Exiting inner loop (SaveAndNext)");
-                             break; // Exit inner loop, proceed to next fix/error
-                        } else {
-                             println!("    Continuing inner loop (SaveAndRedo)");
-                            // SaveAndRedo: loop continues
-                        }
-                     } // End SaveAnd* match arm
-                 } // End match app_state.exit_intent
-             } // End inner loop (SaveAndRedo)
-         } // End loop over existing fixes
-     } // End loop over errors_and_fixes_to_process
+    // What the session did to each benchmark
+    let mut edits: Vec<BenchmarkEdit> = Vec::new();
+    for ((app_state, outcome), saved) in app_states.iter().zip(&outcomes).zip(&mut saved_fixes) {
+        let edit = match (app_state, outcome) {
+            (_, SessionOutcome::Deleted) => BenchmarkEdit::Deleted,
+            // Quitting only abandons the changes made since the last save
+            (_, SessionOutcome::Quit) => match saved.take() {
+                Some(fixes) => BenchmarkEdit::Edited(fixes),
+                None => BenchmarkEdit::Unchanged,
+            },
+            (_, SessionOutcome::Skipped) | (None, _) => BenchmarkEdit::Unchanged,
+            // Errors left by jumping away keep their unsaved changes
+            (Some(app_state), SessionOutcome::Visited | SessionOutcome::Unvisited)
+                if !app_state.is_modified() =>
+            {
+                BenchmarkEdit::Unchanged
+            }
+            (Some(app_state), _) => match app_state.fixes(dir_path) {
+                Ok(fixes) => BenchmarkEdit::Edited(fixes),
+                Err(e) => {
+                    eprintln!("      Error collecting fixes from TUI state: {}", e);
+                    BenchmarkEdit::Unchanged
+                }
+            },
+        };
+        edits.push(edit);
+    }
 
     // Restore terminal state regardless of how TUI exited
     disable_raw_mode()?;
     stdout().execute(LeaveAlternateScreen)?;
     println!("TUI finished.");
 
-    // --- Save the collected/updated benchmarks ---
-    // Convert map values back to a Vec
-    let final_benchmarks_to_write: Vec<ErrorAndFixes> =
-        final_benchmarks_map.into_values().collect();
+    // --- Work out what changes in the suite ---
+    let existing_files = suite.benchmark_files()?;
+    let has_file = |error_name: &str| {
+        existing_files
+            .iter()
+            .any(|path| path.file_stem().is_some_and(|stem| stem == error_name))
+    };
+    let mut changes = SuiteChanges::default();
+    for (mut benchmark, edit) in errors_and_fixes_to_process.into_iter().zip(edits) {
+        match edit {
+            BenchmarkEdit::Deleted => {
+                if has_file(&benchmark.error_name) {
+                    changes.deleted.push(benchmark.error_name);
+                }
+            }
+            BenchmarkEdit::Unchanged if has_file(&benchmark.error_name) => changes.num_unchanged += 1,
+            BenchmarkEdit::Unchanged => changes.num_not_added += 1,
+            BenchmarkEdit::Edited(fixes) => {
+                let is_modified = fixes != benchmark.fixes;
+                benchmark.fixes = fixes;
+                if !has_file(&benchmark.error_name) {
+                    changes.added.push(benchmark);
+                } else if is_modified {
+                    changes.modified.push(benchmark);
+                } else {
+                    changes.num_unchanged += 1;
+                }
+            }
+        }
+    }
 
-    if !final_benchmarks_to_write.is_empty() {
-        println!(
-            "Writing {} benchmarks to suite: {:?}",
-            final_benchmarks_to_write.len(),
-            suite.path()
-        );
-         // This overwrites the entire suite's benchmarks with the ones processed/saved in the TUI
-         // It also updates git-info.json
-         suite.write_benchmarks(
-             &final_benchmarks_to_write,
-             git_info, // Pass the git_info used for this session
-         )?;
-    } else if !exit_tui {
-         // If TUI wasn't quit, but no benchmarks were saved (e.g., all skipped, or resulted in empty fixes)
-         // We should still update git_info if it might have changed (e.g., 'add' context).
-         // For 'edit', git_info is likely the same, but writing doesn't hurt.
-         // We pass an empty benchmark list, so `write_benchmarks` effectively just updates git_info.
-         println!("No benchmarks saved from TUI. Writing potentially updated git-info...");
-         suite.write_benchmarks(&[], git_info)?;
-     } else {
-         println!("TUI was quit. No benchmarks written.");
-     }
+    // --- Save the changes ---
+    if changes.is_empty() {
+        // Still write git-info, which may have changed (e.g. in the 'add' context)
+        println!("No benchmark changes from TUI. Writing potentially updated git-info...");
+        suite.write_benchmarks(&[], git_info)?;
+        return Ok(());
+    }
+    println!("{}", changes.preview(&suite));
+    if !confirm("Apply these changes?")? {
+        println!("Discarded the changes. No benchmarks written.");
+        return Ok(());
+    }
+    let to_write: Vec<ErrorAndFixes> = changes.added.into_iter().chain(changes.modified).collect();
+    println!("Writing {} benchmarks to suite: {:?}", to_write.len(), suite.path());
+    suite.write_benchmarks(&to_write, git_info)?;
+    suite.delete_benchmarks(&changes.deleted)?;
 
     Ok(())
 }
@@ -0,0 +1,453 @@
+use crate::types::{match_errors, ErrorAndFixes, Fix, FixLine, LineLoc};
+use anyhow::{Context, Result};
+use git2::{DiffFindOptions, DiffOptions, Oid, Patch, Repository};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// A hunk of a diff, in the 1-indexed line numbers git uses. With zero lines of
+/// context, a hunk with `old_lines == 0` is an insertion *after* `old_start`.
+#[derive(Debug, Clone, Copy)]
+struct Hunk {
+    old_start: usize,
+    old_lines: usize,
+    new_lines: usize,
+}
+
+/// How a file changed between the two commits.
+#[derive(Debug)]
+struct FileChange {
+    /// The path in the new commit, or `None` if the file was deleted
+    new_path: Option<PathBuf>,
+    /// Sorted by `old_start`
+    hunks: Vec<Hunk>,
+}
+
+/// Maps line locations in an old commit to the same lines in a new commit,
+/// using the diff hunks between the two.
+pub struct LineRemapper {
+    /// Files changed between the commits, keyed by their (repo-root-relative)
+    /// path in the old commit. Files not in here are unchanged.
+    changes: HashMap<PathBuf, FileChange>,
+}
+
+impl LineRemapper {
+    /// Diffs `old_commit` against `new_commit` in `repo` (with rename detection).
+    pub fn new(repo: &Repository, old_commit: &str, new_commit: &str) -> Result<Self> {
+        let tree_of = |commit: &str| -> Result<git2::Tree<'_>> {
+            let oid = Oid::from_str(commit).with_context(|| format!("Invalid commit hash: {}", commit))?;
+            repo.find_commit(oid)
+                .with_context(|| format!("Commit {} not found in {:?}", commit, repo.path()))?
+                .tree()
+                .with_context(|| format!("Failed to get tree of commit {}", commit))
+        };
+        let old_tree = tree_of(old_commit)?;
+        let new_tree = tree_of(new_commit)?;
+
+        let mut diff_options = DiffOptions::new();
+        diff_options.context_lines(0);
+        let mut diff = repo
+            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut diff_options))
+            .context("Failed to diff the commits")?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+            .context("Failed to detect renames")?;
+
+        let mut changes = HashMap::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            // Added files have no old lines to remap
+            let Some(old_path) = delta.old_file().path().filter(|_| delta.old_file().exists()) else {
+                continue;
+            };
+            let new_path = delta
+                .new_file()
+                .path()
+                .filter(|_| delta.new_file().exists())
+                .map(Path::to_path_buf);
+
+            let mut hunks = vec![];
+            if let Some(patch) = Patch::from_diff(&diff, idx).context("Failed to build patch")? {
+                for hunk_idx in 0..patch.num_hunks() {
+                    let (hunk, _) = patch.hunk(hunk_idx).context("Failed to read diff hunk")?;
+                    hunks.push(Hunk {
+                        old_start: hunk.old_start() as usize,
+                        old_lines: hunk.old_lines() as usize,
+                        new_lines: hunk.new_lines() as usize,
+                    });
+                }
+            }
+            hunks.sort_by_key(|hunk| hunk.old_start);
+            changes.insert(old_path.to_path_buf(), FileChange { new_path, hunks });
+        }
+        Ok(Self { changes })
+    }
+
+    /// Maps a line of a (repo-root-relative) file in the old commit to the new
+    /// commit. Returns `None` if the line was changed or deleted (including
+    /// when the whole file was deleted).
+    pub fn remap(&self, file: &Path, line: usize) -> Option<(PathBuf, usize)> {
+        let Some(change) = self.changes.get(file) else {
+            return Some((file.to_path_buf(), line));
+        };
+        let new_path = change.new_path.clone()?;
+
+        let mut new_line = line;
+        for hunk in &change.hunks {
+            if hunk.old_lines == 0 {
+                // Pure insertion after `old_start`
+                if line > hunk.old_start {
+                    new_line += hunk.new_lines;
+                } else {
+                    break;
+                }
+            } else if line < hunk.old_start {
+                break;
+            } else if line < hunk.old_start + hunk.old_lines {
+                // The line itself was modified or removed
+                return None;
+            } else {
+                new_line = new_line + hunk.new_lines - hunk.old_lines;
+            }
+        }
+        Some((new_path, new_line))
+    }
+
+    /// Like `remap`, but for files relative to `subdir` (the directory flux
+    /// runs in), as `FixLine`s and `error_lines` are.
+    fn remap_in_subdir(&self, subdir: &Path, file: &Path, line: usize) -> Option<(PathBuf, usize)> {
+        let (new_file, new_line) = self.remap(&subdir.join(file), line)?;
+        let relative = pathdiff::diff_paths(&new_file, subdir)?;
+        Some((relative, new_line))
+    }
+}
+
+/// A benchmark carried over to the new commit.
+pub struct PortedBenchmark {
+    /// The new error (named as flux names it at the new commit) with the
+    /// remapped fixes
+    pub benchmark: ErrorAndFixes,
+    /// Name of the benchmark in the old suite
+    pub old_error_name: String,
+    /// Fix lines that couldn't be remapped and were dropped
+    pub dropped_lines: Vec<LineLoc>,
+}
+
+impl PortedBenchmark {
+    /// Whether every fix line was carried over
+    pub fn is_clean(&self) -> bool {
+        self.dropped_lines.is_empty()
+    }
+}
+
+/// The result of porting a suite's benchmarks to a new commit.
+pub struct PortResult {
+    pub ported: Vec<PortedBenchmark>,
+    /// Old benchmarks that flux doesn't report at the new commit
+    pub not_reproduced: Vec<String>,
+    /// Errors at the new commit with no benchmark in the old suite
+    pub new_errors: Vec<String>,
+}
+
+/// Carries the fixes of `old_benchmarks` over to the errors flux reports at the
+/// new commit (`new_errors`). Errors are matched by fingerprint (see
+/// `match_errors`); errors whose fingerprint changed are matched if their
+/// remapped error lines are the same.
+///
+/// # Arguments
+/// * `old_benchmarks`: The benchmarks of the old suite.
+/// * `new_errors`: The errors flux reports at the new commit.
+/// * `remapper`: Maps lines from the old to the new commit.
+/// * `subdir`: The directory flux runs in, relative to the repo root.
+pub fn port_benchmarks(
+    old_benchmarks: &[ErrorAndFixes],
+    new_errors: Vec<ErrorAndFixes>,
+    remapper: &LineRemapper,
+    subdir: &Path,
+) -> PortResult {
+    // The names contain the short commit hash, so matching by name would
+    // never succeed; the fingerprints are all that's left of `match_errors`.
+    let mut matches = match_errors(&new_errors, old_benchmarks);
+    let mut used = vec![false; old_benchmarks.len()];
+    for old_idx in matches.iter().flatten() {
+        used[*old_idx] = true;
+    }
+
+    // Fall back to the error lines for errors whose message or span text changed
+    let remapped_error_lines: Vec<Option<Vec<(PathBuf, usize)>>> = old_benchmarks
+        .iter()
+        .map(|benchmark| {
+            benchmark
+                .error_lines
+                .iter()
+                .map(|loc| remapper.remap_in_subdir(subdir, &loc.file, loc.line))
+                .collect()
+        })
+        .collect();
+    for (new_idx, new_error) in new_errors.iter().enumerate() {
+        if matches[new_idx].is_some() {
+            continue;
+        }
+        let new_lines: Vec<(PathBuf, usize)> = new_error
+            .error_lines
+            .iter()
+            .map(|loc| (loc.file.clone(), loc.line))
+            .collect();
+        if let Some(old_idx) = (0..old_benchmarks.len())
+            .find(|&idx| !used[idx] && remapped_error_lines[idx].as_ref() == Some(&new_lines))
+        {
+            matches[new_idx] = Some(old_idx);
+            used[old_idx] = true;
+        }
+    }
+
+    let mut result = PortResult {
+        ported: vec![],
+        not_reproduced: old_benchmarks
+            .iter()
+            .zip(&used)
+            .filter(|(_, used)| !**used)
+            .map(|(benchmark, _)| benchmark.error_name.clone())
+            .collect(),
+        new_errors: vec![],
+    };
+
+    for (mut new_error, old_idx) in new_errors.into_iter().zip(matches) {
+        let Some(old_idx) = old_idx else {
+            result.new_errors.push(new_error.error_name);
+            continue;
+        };
+        let old_benchmark = &old_benchmarks[old_idx];
+        let mut dropped_lines = vec![];
+        new_error.fixes = old_benchmark
+            .fixes
+            .iter()
+            .map(|fix| Fix {
+                fix_lines: fix
+                    .fix_lines
+                    .iter()
+                    .filter_map(|fix_line| {
+                        match remapper.remap_in_subdir(subdir, &fix_line.file, fix_line.line) {
+                            Some((file, line)) => Some(FixLine {
+                                line,
+                                file,
+                                // Columns may have moved within a changed line,
+                                // but unchanged lines keep their columns.
+                                ..fix_line.clone()
+                            }),
+                            None => {
+                                dropped_lines.push(LineLoc::new(fix_line.line, fix_line.file.clone()));
+                                None
+                            }
+                        }
+                    })
+                    .collect(),
+                note: fix.note.clone(),
+                is_trivial: fix.is_trivial,
+            })
+            .collect();
+        result.ported.push(PortedBenchmark {
+            benchmark: new_error,
+            old_error_name: old_benchmark.error_name.clone(),
+            dropped_lines,
+        });
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::tests::error_and_fixes;
+
+    fn hunk(old_start: usize, old_lines: usize, new_lines: usize) -> Hunk {
+        Hunk { old_start, old_lines, new_lines }
+    }
+
+    /// A remapper for a single changed file.
+    fn remapper(old_path: &str, new_path: Option<&str>, hunks: Vec<Hunk>) -> LineRemapper {
+        let change = FileChange { new_path: new_path.map(PathBuf::from), hunks };
+        LineRemapper { changes: HashMap::from([(PathBuf::from(old_path), change)]) }
+    }
+
+    fn remap(remapper: &LineRemapper, file: &str, line: usize) -> Option<(PathBuf, usize)> {
+        remapper.remap(Path::new(file), line)
+    }
+
+    #[test]
+    fn remap_keeps_unchanged_files() {
+        let remapper = remapper("src/lib.rs", Some("src/lib.rs"), vec![hunk(1, 1, 1)]);
+        assert_eq!(remap(&remapper, "src/other.rs", 1), Some(("src/other.rs".into(), 1)));
+    }
+
+    #[test]
+    fn remap_shifts_lines_after_a_replaced_hunk() {
+        // Lines 5-6 were replaced by 3 lines
+        let remapper = remapper("src/lib.rs", Some("src/lib.rs"), vec![hunk(5, 2, 3)]);
+        assert_eq!(remap(&remapper, "src/lib.rs", 4), Some(("src/lib.rs".into(), 4)));
+        assert_eq!(remap(&remapper, "src/lib.rs", 5), None);
+        assert_eq!(remap(&remapper, "src/lib.rs", 6), None);
+        assert_eq!(remap(&remapper, "src/lib.rs", 7), Some(("src/lib.rs".into(), 8)));
+    }
+
+    #[test]
+    fn remap_shifts_lines_after_a_deletion() {
+        // Lines 3-4 were deleted
+        let remapper = remapper("src/lib.rs", Some("src/lib.rs"), vec![hunk(3, 2, 0)]);
+        assert_eq!(remap(&remapper, "src/lib.rs", 2), Some(("src/lib.rs".into(), 2)));
+        assert_eq!(remap(&remapper, "src/lib.rs", 4), None);
+        assert_eq!(remap(&remapper, "src/lib.rs", 5), Some(("src/lib.rs".into(), 3)));
+    }
+
+    #[test]
+    fn remap_keeps_the_line_an_insertion_follows() {
+        // Two lines were inserted after line 4
+        let remapper = remapper("src/lib.rs", Some("src/lib.rs"), vec![hunk(4, 0, 2)]);
+        assert_eq!(remap(&remapper, "src/lib.rs", 4), Some(("src/lib.rs".into(), 4)));
+        assert_eq!(remap(&remapper, "src/lib.rs", 5), Some(("src/lib.rs".into(), 7)));
+    }
+
+    #[test]
+    fn remap_shifts_every_line_after_an_insertion_at_the_top() {
+        let remapper = remapper("src/lib.rs", Some("src/lib.rs"), vec![hunk(0, 0, 2)]);
+        assert_eq!(remap(&remapper, "src/lib.rs", 1), Some(("src/lib.rs".into(), 3)));
+    }
+
+    #[test]
+    fn remap_accumulates_the_shifts_of_earlier_hunks() {
+        let remapper = remapper(
+            "src/lib.rs",
+            Some("src/lib.rs"),
+            vec![hunk(2, 0, 3), hunk(5, 1, 0), hunk(10, 2, 1)],
+        );
+        assert_eq!(remap(&remapper, "src/lib.rs", 4), Some(("src/lib.rs".into(), 7)));
+        assert_eq!(remap(&remapper, "src/lib.rs", 5), None);
+        assert_eq!(remap(&remapper, "src/lib.rs", 8), Some(("src/lib.rs".into(), 10)));
+        assert_eq!(remap(&remapper, "src/lib.rs", 12), Some(("src/lib.rs".into(), 13)));
+    }
+
+    #[test]
+    fn remap_follows_renames_and_drops_deleted_files() {
+        let renamed = remapper("src/old.rs", Some("src/new.rs"), vec![hunk(1, 0, 1)]);
+        assert_eq!(remap(&renamed, "src/old.rs", 3), Some(("src/new.rs".into(), 4)));
+
+        let deleted = remapper("src/old.rs", None, vec![hunk(1, 10, 0)]);
+        assert_eq!(remap(&deleted, "src/old.rs", 3), None);
+    }
+
+    #[test]
+    fn remap_in_subdir_keeps_paths_relative_to_the_subdir() {
+        let remapper = remapper("crate/src/old.rs", Some("crate/src/new.rs"), vec![]);
+        assert_eq!(
+            remapper.remap_in_subdir(Path::new("crate"), Path::new("src/old.rs"), 3),
+            Some(("src/new.rs".into(), 3))
+        );
+    }
+
+    /// Commits `files` (path and content, `None` to delete) on top of HEAD.
+    fn commit(repo: &Repository, files: &[(&str, Option<&str>)]) -> String {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            match content {
+                Some(content) => {
+                    std::fs::write(workdir.join(path), content).unwrap();
+                    index.add_path(Path::new(path)).unwrap();
+                }
+                None => {
+                    std::fs::remove_file(workdir.join(path)).unwrap();
+                    index.remove_path(Path::new(path)).unwrap();
+                }
+            }
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repo.commit(Some("HEAD"), &signature, &signature, "commit", &tree, &parent.iter().collect::<Vec<_>>())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn new_diffs_the_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let lines = |n: usize, prefix: &str| (1..=n).map(|i| format!("{} {}\n", prefix, i)).collect::<Vec<_>>().concat();
+        let old_commit = commit(
+            &repo,
+            &[
+                ("a.rs", Some(&lines(10, "a"))),
+                ("b.rs", Some(&lines(10, "b"))),
+                ("c.rs", Some(&lines(10, "c"))),
+            ],
+        );
+        let new_a = format!("new 1\nnew 2\n{}", lines(10, "a"));
+        let new_commit = commit(
+            &repo,
+            &[("a.rs", Some(&new_a)), ("b.rs", None), ("c.rs", None), ("d.rs", Some(&lines(10, "c")))],
+        );
+
+        let remapper = LineRemapper::new(&repo, &old_commit, &new_commit).unwrap();
+        assert_eq!(remap(&remapper, "a.rs", 1), Some(("a.rs".into(), 3)));
+        assert_eq!(remap(&remapper, "b.rs", 1), None);
+        assert_eq!(remap(&remapper, "c.rs", 5), Some(("d.rs".into(), 5)));
+    }
+
+    fn with_error_line(mut error: ErrorAndFixes, fingerprint: &str, file: &str, line: usize) -> ErrorAndFixes {
+        error.fingerprint = Some(fingerprint.to_string());
+        error.error_lines.push_back(LineLoc::new(line, file.into()));
+        error
+    }
+
+    fn fix_line(file: &str, line: usize) -> FixLine {
+        FixLine { line, file: file.into(), added_reft: None, columns: None }
+    }
+
+    #[test]
+    fn port_benchmarks_matches_by_fingerprint_then_by_error_lines() {
+        // Two lines were inserted after line 4, and line 20 changed
+        let remapper = remapper("crate/src/lib.rs", Some("crate/src/lib.rs"), vec![hunk(4, 0, 2), hunk(20, 1, 1)]);
+        let mut moved = with_error_line(error_and_fixes("old-moved", "error"), "1", "src/lib.rs", 10);
+        moved.fixes.push(Fix {
+            fix_lines: vec![fix_line("src/lib.rs", 3), fix_line("src/lib.rs", 8), fix_line("src/lib.rs", 20)],
+            note: Some("note".to_string()),
+            is_trivial: Some(true),
+        });
+        let old_benchmarks = vec![
+            moved,
+            with_error_line(error_and_fixes("old-changed", "error"), "2", "src/lib.rs", 15),
+            with_error_line(error_and_fixes("old-fixed", "error"), "3", "src/lib.rs", 30),
+        ];
+        let new_errors = vec![
+            // The message changed, but it's still on the (remapped) same line
+            with_error_line(error_and_fixes("new-changed", "error"), "changed", "src/lib.rs", 17),
+            with_error_line(error_and_fixes("new-moved", "error"), "1", "src/lib.rs", 12),
+            with_error_line(error_and_fixes("new-error", "error"), "4", "src/lib.rs", 16),
+        ];
+
+        let result = port_benchmarks(&old_benchmarks, new_errors, &remapper, Path::new("crate"));
+
+        let ported: Vec<(&str, &str)> = result
+            .ported
+            .iter()
+            .map(|ported| (ported.old_error_name.as_str(), ported.benchmark.error_name.as_str()))
+            .collect();
+        assert_eq!(ported, vec![("old-changed", "new-changed"), ("old-moved", "new-moved")]);
+        assert_eq!(result.not_reproduced, vec!["old-fixed"]);
+        assert_eq!(result.new_errors, vec!["new-error"]);
+
+        let moved = &result.ported[1];
+        assert_eq!(
+            moved.benchmark.fixes,
+            vec![Fix {
+                fix_lines: vec![fix_line("src/lib.rs", 3), fix_line("src/lib.rs", 10)],
+                note: Some("note".to_string()),
+                is_trivial: Some(true),
+            }]
+        );
+        assert_eq!(moved.dropped_lines, vec![LineLoc::new(20, "src/lib.rs".into())]);
+        assert!(!moved.is_clean());
+        assert!(result.ported[0].is_clean());
+    }
+}
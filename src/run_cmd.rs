@@ -1,4 +1,4 @@
-use crate::types::{CompilerMessage, ErrorAndFixes, GitInformation, RemoteInfo};
+use crate::types::{error_fingerprint, CompilerMessage, ErrorAndFixes, GitInformation, RemoteInfo};
 use anyhow::{bail, Context, Result};
 use git2::{
     ErrorCode, FetchOptions, FetchPrune, Oid, ReferenceType, RemoteCallbacks, Repository, Status,
@@ -422,7 +422,16 @@ pub fn discover_git_info(directory: &Path) -> Result<(GitInformation, PathBuf)>
     Ok((git_info, repo_root.to_path_buf()))
 }
 
+/// Runs `cargo flux` in `directory` and parses its errors. See
+/// `run_flux_raw` and `parse_flux_output`.
 pub fn run_flux_in_dir(directory: &Path, commit_hash: &str, get_diagnostics: bool) -> Result<Vec<ErrorAndFixes>> {
+    let lines = run_flux_raw(directory, get_diagnostics)?;
+    parse_flux_output(directory, commit_hash, &lines)
+}
+
+/// Runs `cargo flux --message-format=json` in `directory` and returns the raw
+/// JSON lines it printed to stdout.
+pub fn run_flux_raw(directory: &Path, get_diagnostics: bool) -> Result<Vec<String>> {
     let canonical_directory = directory.canonicalize().with_context(|| {
         format!(
             "Failed to canonicalize directory path: {}",
@@ -458,14 +467,63 @@ pub fn run_flux_in_dir(directory: &Path, commit_hash: &str, get_diagnostics: boo
     })?;
 
     let reader = BufReader::new(stdout_handle);
+    let lines = reader
+        .lines()
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to read line from command stdout: {}", command_desc))?;
+
+    child
+        .wait()
+        .with_context(|| format!("Failed to wait for command process: {}", command_desc))?;
+
+    // We expect flux to fail, so we won't bother to deal with this.
+    // if !status.success() {
+    //     bail!("Command failed with status {}: {}", status, command_desc);
+    // }
+    info!("Command ran: {}", command_desc);
+
+    Ok(lines)
+}
+
+/// Returns the version string reported by `cargo flux --version`, which
+/// identifies the flux build that produced an output.
+pub fn flux_version() -> Result<String> {
+    let output = Command::new("cargo")
+        .args(["flux", "--version"])
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to run: cargo flux --version")?;
+    if !output.status.success() {
+        bail!("cargo flux --version failed with status {}", output.status);
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version.is_empty() {
+        bail!("cargo flux --version printed nothing");
+    }
+    Ok(version)
+}
+
+/// Builds the errors from the JSON lines printed by `cargo flux
+/// --message-format=json` (as returned by `run_flux_raw`).
+///
+/// # Arguments
+/// * `directory`: The directory flux ran in; used to look up the names of the
+///                functions containing the errors.
+/// * `commit_hash`: The commit flux ran on; used in the error names.
+/// * `lines`: The raw JSON lines.
+pub fn parse_flux_output(directory: &Path, commit_hash: &str, lines: &[String]) -> Result<Vec<ErrorAndFixes>> {
+    let canonical_directory = directory.canonicalize().with_context(|| {
+        format!(
+            "Failed to canonicalize directory path: {}",
+            directory.display()
+        )
+    })?;
+
     let mut errors = Vec::new();
     let mut error_name_numbers: HashMap<String, usize> = HashMap::new();
     // Build a Vec<ErrorAndFix> from the output (the fix is, of course, empty).
-    for line_result in reader.lines() {
-        let line = line_result.with_context(|| {
-            format!("Failed to read line from command stdout: {}", command_desc)
-        })?;
-        let json: serde_json::Value = serde_json::from_str(&line)?;
+    for line in lines {
+        let json: serde_json::Value = serde_json::from_str(line)?;
         // Does it have a "reason" field and is that reason "compiler-message"
         if !json
             .as_object()
@@ -478,7 +536,7 @@ pub fn run_flux_in_dir(directory: &Path, commit_hash: &str, get_diagnostics: boo
             info!("Skipping line because it doesn't match the expected structure of a Rust compiler message: {}", line);
             continue;
         }
-        let error: CompilerMessage = serde_json::from_str(&line)?;
+        let error: CompilerMessage = serde_json::from_str(line)?;
         if error.message.level != "error" {
             continue;
         }
@@ -514,8 +572,10 @@ pub fn run_flux_in_dir(directory: &Path, commit_hash: &str, get_diagnostics: boo
                 // Otherwise, this is the first
                 .or_insert(1);
             let full_error_name = format!("{}-{}", error_name, error_name_entry);
+            let fingerprint = error_fingerprint(&containing_fn_name, &error);
             errors.push(ErrorAndFixes {
                 error_name: full_error_name,
+                fingerprint: Some(fingerprint),
                 error,
                 // No fixes yet
                 fixes: vec![],
@@ -525,16 +585,6 @@ pub fn run_flux_in_dir(directory: &Path, commit_hash: &str, get_diagnostics: boo
         }
     }
 
-    child
-        .wait()
-        .with_context(|| format!("Failed to wait for command process: {}", command_desc))?;
-
-    // We expect flux to fail, so we won't bother to deal with this.
-    // if !status.success() {
-    //     bail!("Command failed with status {}: {}", status, command_desc);
-    // }
-    info!("Command ran: {}", command_desc);
-
     Ok(errors)
 }
 
@@ -1,9 +1,10 @@
-use crate::types::{ErrorAndFixes, Fix, FixLine, LineLoc};
+use crate::evaluator::{ConstraintDebugInfoDeserialize, ErrorEvalResult, SimpleSpanDeserialize};
+use crate::types::{ColumnRange, Diagnostic, ErrorAndFixes, Fix, FixLine, LineLoc};
 use anyhow::{Context, Result};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Clear, Paragraph, Wrap}, // Added Clear
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap}, // Added Clear
 };
 use ratatui_explorer::FileExplorer;
 use std::{
@@ -31,6 +32,7 @@ pub enum AppMode {
     AddNote,
     FileExplorer,
     ConfirmationDialog,
+    ErrorNavigator,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,10 +41,12 @@ enum ConfirmationChoice {
     No,
 }
 
+type ConfirmationAction = Box<dyn FnOnce(&mut AppState, bool) -> Result<()>>;
+
 struct ConfirmationState {
     title: String,
     body: Option<String>,
-    action_on_confirm: Box<dyn FnOnce(&mut AppState, bool) -> Result<()>>,
+    action_on_confirm: ConfirmationAction,
     current_choice: ConfirmationChoice,
     mode_on_exit: AppMode,
 }
@@ -51,12 +55,310 @@ struct ConfirmationState {
 pub enum ExitIntent {
     /// Quit the entire TUI
     Quit,
-    /// Record the fix lines and do this error again
-    SaveAndRedo,
-    /// Record the fix lines and proceed to next error
+    /// Record the fixes and proceed to next error
     SaveAndNext,
     /// Skip and proceed to next error
     Skip,
+    /// Delete the benchmark from the suite and proceed to next error
+    Delete,
+    /// Leave this error (keeping its changes) and go to the error with this
+    /// index in the session
+    JumpTo(usize),
+}
+
+/// The annotation status of an error in the session, shown in the navigator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorStatus {
+    Unannotated,
+    HasFixes,
+    /// Has a fix marked as trivial
+    Trivial,
+    Skipped,
+    Deleted,
+}
+
+impl ErrorStatus {
+    /// The status of an error with these fixes (that wasn't skipped or deleted).
+    pub fn of_fixes(fixes: &[Fix]) -> Self {
+        if fixes.iter().any(|fix| fix.is_trivial == Some(true)) {
+            ErrorStatus::Trivial
+        } else if fixes.iter().any(|fix| !fix.fix_lines.is_empty() || fix.note.is_some()) {
+            ErrorStatus::HasFixes
+        } else {
+            ErrorStatus::Unannotated
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            ErrorStatus::Unannotated => Color::Yellow,
+            ErrorStatus::HasFixes => Color::Green,
+            ErrorStatus::Trivial => Color::Cyan,
+            ErrorStatus::Skipped => Color::DarkGray,
+            ErrorStatus::Deleted => Color::Red,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ErrorStatus::Unannotated => "unannotated",
+            ErrorStatus::HasFixes => "has fixes",
+            ErrorStatus::Trivial => "trivial",
+            ErrorStatus::Skipped => "skipped",
+            ErrorStatus::Deleted => "deleted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An error of the session, as listed in the navigator.
+#[derive(Debug, Clone)]
+pub struct SessionError {
+    pub error_name: String,
+    pub status: ErrorStatus,
+    /// Whether the error has changes that aren't saved yet
+    pub is_modified: bool,
+}
+
+/// Whether flux's debug info found a fix line, as shown when reviewing an
+/// `eval` run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineHit {
+    /// A blame span contains the line
+    BlameSpan,
+    /// Only the span of a binder (or its function) contains the line
+    AllBinders,
+    Missed,
+}
+
+impl LineHit {
+    fn color(self) -> Color {
+        match self {
+            LineHit::BlameSpan => Color::Rgb(30, 90, 40),
+            LineHit::AllBinders => Color::Rgb(110, 90, 20),
+            LineHit::Missed => Color::Rgb(120, 30, 30),
+        }
+    }
+}
+
+/// The eval result of one fix of the error being reviewed.
+#[derive(Debug, Clone, Default)]
+struct FixReview {
+    /// Absolute
+    line_hits: BTreeMap<LineLoc, LineHit>,
+    /// The fix lines no blame span contains (`FixEvalResult.missing_lines`),
+    /// absolute
+    missing_lines: Vec<LineLoc>,
+}
+
+impl FixReview {
+    fn num_hits(&self, hit: LineHit) -> usize {
+        self.line_hits.values().filter(|line_hit| **line_hit == hit).count()
+    }
+}
+
+/// One of the alternative fixes of the error being annotated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FixAnnotation {
+    fix_lines: BTreeMap<LineLoc, Option<String>>, // 1-indexed line number to fix text (None means there is a fix but isn't provided)
+    /// Column ranges of fix lines loaded from the benchmark. They can't be
+    /// edited here, but are kept so that saving doesn't drop them.
+    fix_columns: BTreeMap<LineLoc, ColumnRange>,
+    note: Option<String>,
+    has_trivial_fix: Option<bool>,
+}
+
+impl FixAnnotation {
+    /// Loads `fix`, making its (run dir relative) paths absolute.
+    fn new(fix: &Fix, dir_path: &Path) -> Self {
+        let line_loc = |fix_line: &FixLine| LineLoc::new(fix_line.line, dir_path.join(&fix_line.file));
+        Self {
+            fix_lines: fix
+                .fix_lines
+                .iter()
+                .map(|fix_line| (line_loc(fix_line), fix_line.added_reft.clone()))
+                .collect(),
+            fix_columns: fix
+                .fix_lines
+                .iter()
+                .filter_map(|fix_line| fix_line.columns.map(|columns| (line_loc(fix_line), columns)))
+                .collect(),
+            note: fix.note.clone(),
+            has_trivial_fix: fix.is_trivial,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.fix_lines.is_empty() && self.note.is_none()
+    }
+
+    fn to_fix(&self, dir_path: &Path) -> Result<Fix> {
+        let fix_lines = self
+            .fix_lines
+            .iter()
+            .map(|(line_loc, fix)| {
+                Ok::<FixLine, anyhow::Error>(FixLine {
+                    line: line_loc.line,
+                    // Make the lines relative to the directory we run in
+                    // This is the convention we adopt for saving other files.
+                    file: pathdiff::diff_paths(&line_loc.file, dir_path).ok_or_else(|| {
+                        anyhow::anyhow!("Couldn't diff path {:?} and {:?}", dir_path, line_loc.file)
+                    })?,
+                    added_reft: fix.clone(),
+                    columns: self.fix_columns.get(line_loc).copied(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Fix {
+            fix_lines,
+            note: self.note.clone(),
+            is_trivial: self.has_trivial_fix,
+        })
+    }
+}
+
+/// Where a `SpanAnnotation` comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpanKind {
+    /// A span of the error itself
+    Error,
+    /// A span of a note or help of the error
+    Child,
+    /// A span flux blamed, by rank (0 is the most likely culprit)
+    Blame(usize),
+    /// The span of a binder of the failing constraint
+    Binder,
+}
+
+impl SpanKind {
+    /// Whether the span is part of the blame overlay rather than the error
+    fn is_blame_overlay(self) -> bool {
+        matches!(self, SpanKind::Blame(_) | SpanKind::Binder)
+    }
+}
+
+/// Colours of the top ranked blame spans; lower ranks are grey.
+const BLAME_COLORS: [Color; 3] = [
+    Color::Rgb(255, 140, 0),
+    Color::Yellow,
+    Color::Rgb(200, 200, 120),
+];
+
+fn blame_color(rank: usize) -> Color {
+    BLAME_COLORS.get(rank).copied().unwrap_or(Color::Gray)
+}
+
+/// A span of the error (or of one of its child diagnostics), drawn under the
+/// source like rustc does. Also used for the spans of flux's debug info.
+#[derive(Debug, Clone)]
+struct SpanAnnotation {
+    /// Absolute
+    file: PathBuf,
+    /// 1-indexed, inclusive
+    line_start: usize,
+    line_end: usize,
+    /// 1-indexed, `column_end` is exclusive (as rustc reports them)
+    column_start: usize,
+    column_end: usize,
+    is_primary: bool,
+    kind: SpanKind,
+    label: Option<String>,
+}
+
+impl SpanAnnotation {
+    /// Collects the spans of `diagnostic` and of its children, recursively.
+    /// Child spans without a label are labelled with the child's message.
+    fn collect(diagnostic: &Diagnostic, dir_path: &Path, kind: SpanKind, spans: &mut Vec<Self>) {
+        let is_child = kind == SpanKind::Child;
+        for span in &diagnostic.spans {
+            let label = span.label.clone().or_else(|| {
+                is_child.then(|| format!("{}: {}", diagnostic.level, diagnostic.message))
+            });
+            spans.push(Self {
+                file: dir_path.join(&span.file_name),
+                line_start: span.line_start,
+                line_end: span.line_end,
+                column_start: span.column_start,
+                column_end: span.column_end,
+                is_primary: span.is_primary,
+                kind,
+                label,
+            });
+        }
+        for child in &diagnostic.children {
+            Self::collect(child, dir_path, SpanKind::Child, spans);
+        }
+    }
+
+    /// The blame spans (in rank order) and binder spans of flux's debug info.
+    fn collect_debug_info(info: &ConstraintDebugInfoDeserialize, dir_path: &Path, spans: &mut Vec<Self>) {
+        for (rank, blame) in info.blame_spans.iter().enumerate() {
+            let Some(span) = &blame.blame_span else {
+                continue;
+            };
+            let mut label = format!("#{} {}", rank + 1, blame.binder_name);
+            if let Some(refinement) = &blame.suggested_refinement {
+                label.push_str(&format!(" => {}", refinement));
+            }
+            spans.push(Self::from_debug_span(span, dir_path, SpanKind::Blame(rank), label));
+        }
+        for binder in &info.binders {
+            let Some(span) = &binder.span else {
+                continue;
+            };
+            let label = format!(
+                "binder {} ({}, depth {})",
+                binder.pretty_name.as_ref().unwrap_or(&binder.name),
+                binder.originator.as_deref().unwrap_or("unknown origin"),
+                binder.depth
+            );
+            spans.push(Self::from_debug_span(span, dir_path, SpanKind::Binder, label));
+        }
+    }
+
+    /// Debug info spans are relative to `dir_path` once resolved (see
+    /// `SpanPathResolver`), and their end column is inclusive.
+    fn from_debug_span(span: &SimpleSpanDeserialize, dir_path: &Path, kind: SpanKind, label: String) -> Self {
+        Self {
+            file: dir_path.join(&span.start.file),
+            line_start: span.start.line,
+            line_end: span.end.line,
+            column_start: span.start.char,
+            column_end: span.end.char + 1,
+            is_primary: false,
+            kind,
+            label: Some(label),
+        }
+    }
+
+    fn style(&self) -> Style {
+        match self.kind {
+            SpanKind::Error if self.is_primary => {
+                Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD)
+            }
+            SpanKind::Error | SpanKind::Child => Style::default().fg(Color::LightBlue),
+            SpanKind::Blame(rank) => Style::default().fg(blame_color(rank)),
+            SpanKind::Binder => Style::default().fg(Color::LightMagenta),
+        }
+    }
+
+    fn marker(&self) -> &'static str {
+        match self.kind {
+            SpanKind::Error | SpanKind::Child if self.is_primary => "^",
+            SpanKind::Error | SpanKind::Child => "-",
+            SpanKind::Blame(_) => "~",
+            SpanKind::Binder => ".",
+        }
+    }
+}
+
+/// The annotation made in the editor, as recorded in the undo history.
+#[derive(Debug, Clone)]
+struct Annotation {
+    fixes: Vec<FixAnnotation>,
+    current_fix: usize,
 }
 
 pub struct AppState {
@@ -68,9 +370,23 @@ pub struct AppState {
     file_locations: BTreeMap<PathBuf, (usize, usize)>,
     lines: Vec<String>,
     error_lines: VecDeque<LineLoc>, // 1-indexed line number of lines with errors
-    // FIXME: make private when we convert the AppState to some kind of output format.
-    pub fix_lines: BTreeMap<LineLoc, Option<String>>, // 1-indexed line number to fix text (None means there is a fix but isn't provided)
-    note: Option<String>,
+    /// Spans of the error to draw under the source
+    span_annotations: Vec<SpanAnnotation>,
+    /// Whether to draw the spans of the error
+    show_spans: bool,
+    /// What flux blamed for the error, if flux ran with `-Fdebug-binder-output`
+    constraint_info: Option<ConstraintDebugInfoDeserialize>,
+    /// Whether to overlay the blame spans and binders of `constraint_info`
+    show_blame: bool,
+    /// The eval result of each fix, when reviewing an `eval` run. The
+    /// annotation can't be changed then.
+    reviews: Option<Vec<FixReview>>,
+    /// Index of the miss of the current fix jumped to last
+    current_miss: Option<usize>,
+    /// The alternative fixes of the error; never empty
+    fixes: Vec<FixAnnotation>,
+    /// Index of the fix being edited
+    current_fix: usize,
     current_line: usize,  // 0-indexed line number currently selected/focused
     scroll_offset: usize, // 0-indexed line number at the top of the viewport
     pub exit_intent: Option<ExitIntent>,
@@ -81,7 +397,16 @@ pub struct AppState {
     editing_line: Option<usize>, // Track which line (1-based) is being edited
     file_explorer: FileExplorer,
     confirmation: Option<ConfirmationState>,
-    has_trivial_fix: Option<bool>,
+    /// Every error of the session, for the navigator
+    session_errors: Vec<SessionError>,
+    /// Index of this error in `session_errors`
+    session_index: usize,
+    /// Error selected in the navigator
+    navigator_selection: usize,
+    /// Annotations before each change, most recent last
+    undo_stack: Vec<Annotation>,
+    /// Annotations undone, most recently undone last
+    redo_stack: Vec<Annotation>,
 }
 
 impl AppState {
@@ -122,24 +447,17 @@ impl AppState {
             .file
             .clone();
 
-        // NOTE: We only will show the first fix: callers must pass
-        // an ErrorAndFixes that has 1 or fewer fix in it or risk
-        // fixes getting swallowed.
-        let (fix_lines, note, has_trivial_fix) = if let Some(fix) = error_and_fixes.fixes.first() {
-            let fix_lines = fix.fix_lines
-                .iter()
-                .map(|fix_line| {
-                    let absolute_path = dir_path.join(&fix_line.file);
-                    (
-                        LineLoc::new(fix_line.line, absolute_path),
-                        fix_line.added_reft.clone(),
-                    )
-                })
-                .collect();
-            (fix_lines, fix.note.clone(), fix.is_trivial)
-        } else {
-            (BTreeMap::new(), None, None)
-        };
+        let mut span_annotations = vec![];
+        SpanAnnotation::collect(&error_and_fixes.error.message, dir_path, SpanKind::Error, &mut span_annotations);
+
+        let mut fixes: Vec<FixAnnotation> = error_and_fixes
+            .fixes
+            .iter()
+            .map(|fix| FixAnnotation::new(fix, dir_path))
+            .collect();
+        if fixes.is_empty() {
+            fixes.push(FixAnnotation::default());
+        }
 
         let mut state = Self {
             error_message: rendered_message,
@@ -148,8 +466,14 @@ impl AppState {
             file_locations: BTreeMap::new(),
             lines: vec![],
             error_lines: error_lines.clone(),
-            fix_lines,
-            note,
+            span_annotations,
+            show_spans: true,
+            constraint_info: None,
+            show_blame: false,
+            reviews: None,
+            current_miss: None,
+            fixes,
+            current_fix: 0,
             current_line: 0,
             scroll_offset: 0,
             exit_intent: None,
@@ -160,7 +484,11 @@ impl AppState {
             editing_line: None,
             file_explorer,
             confirmation: None,
-            has_trivial_fix,
+            session_errors: vec![],
+            session_index: 0,
+            navigator_selection: 0,
+            undo_stack: vec![],
+            redo_stack: vec![],
         };
 
         state.next_error()?;
@@ -168,6 +496,242 @@ impl AppState {
         Ok(state)
     }
 
+    /// Adds what flux blamed for the error, shown as an overlay (toggled with
+    /// `b`). The spans of `constraint_info` must be relative to `dir_path`.
+    pub fn set_constraint_info(&mut self, constraint_info: ConstraintDebugInfoDeserialize, dir_path: &Path) {
+        self.span_annotations.retain(|span| !span.kind.is_blame_overlay());
+        SpanAnnotation::collect_debug_info(&constraint_info, dir_path, &mut self.span_annotations);
+        self.constraint_info = Some(constraint_info);
+        self.show_blame = true;
+    }
+
+    /// Shows how `eval_result` scored the fixes of the error and makes the
+    /// annotation read-only, starting at the first missed fix line.
+    pub fn set_eval_result(&mut self, eval_result: &ErrorEvalResult, dir_path: &Path) -> Result<()> {
+        let absolute = |loc: &LineLoc| LineLoc::new(loc.line, dir_path.join(&loc.file));
+        let reviews = self
+            .fixes
+            .iter()
+            .enumerate()
+            .map(|(fix_idx, fix)| {
+                let Some(fix_eval) = eval_result.fix_evals.get(fix_idx) else {
+                    return FixReview::default();
+                };
+                let missing_lines: Vec<LineLoc> = fix_eval.missing_lines.iter().map(absolute).collect();
+                let missing_lines_all_binders: Vec<LineLoc> =
+                    fix_eval.missing_lines_all_binders.iter().map(absolute).collect();
+                let line_hits = fix
+                    .fix_lines
+                    .keys()
+                    .map(|line_loc| {
+                        let hit = if !missing_lines.contains(line_loc) {
+                            LineHit::BlameSpan
+                        } else if !missing_lines_all_binders.contains(line_loc) {
+                            LineHit::AllBinders
+                        } else {
+                            LineHit::Missed
+                        };
+                        (line_loc.clone(), hit)
+                    })
+                    .collect();
+                FixReview {
+                    line_hits,
+                    missing_lines,
+                }
+            })
+            .collect();
+        self.reviews = Some(reviews);
+        self.current_miss = None;
+        self.go_to_next_miss(true)
+    }
+
+    fn review(&self) -> Option<&FixReview> {
+        self.reviews.as_ref()?.get(self.current_fix)
+    }
+
+    /// How the line fared in the eval being reviewed, if it's a line of the
+    /// current fix.
+    fn line_hit(&self, line_loc: &LineLoc) -> Option<LineHit> {
+        self.review()?.line_hits.get(line_loc).copied()
+    }
+
+    /// Jumps to the next (or previous) missed line of the current fix,
+    /// wrapping around.
+    fn go_to_next_miss(&mut self, forward: bool) -> Result<()> {
+        let Some(review) = self.review() else {
+            return Ok(());
+        };
+        let num_misses = review.missing_lines.len();
+        if num_misses == 0 {
+            return Ok(());
+        }
+        let miss_idx = match self.current_miss {
+            Some(miss_idx) if forward => (miss_idx + 1) % num_misses,
+            Some(miss_idx) => (miss_idx + num_misses - 1) % num_misses,
+            None if forward => 0,
+            None => num_misses - 1,
+        };
+        let miss = review.missing_lines[miss_idx].clone();
+        self.current_miss = Some(miss_idx);
+        self.go_to_file(miss.file)?;
+        self.go_to_line(miss.line);
+        Ok(())
+    }
+
+    /// Does nothing without blame information (see `add --blame`)
+    fn toggle_blame(&mut self) {
+        self.show_blame = !self.show_blame && self.constraint_info.is_some();
+    }
+
+    /// Height of the panel listing the constraint and its blame spans, or 0
+    /// if it isn't shown.
+    fn blame_panel_height(&self) -> u16 {
+        match &self.constraint_info {
+            // Borders, the constraint and a row per blame span
+            Some(info) if self.show_blame => (3 + info.blame_spans.len()).min(10) as u16,
+            _ => 0,
+        }
+    }
+
+    /// The best rank of the blame spans covering line `line_num` (1-indexed)
+    /// of the current file, if the overlay is shown.
+    fn blame_rank(&self, line_num: usize) -> Option<usize> {
+        if !self.show_blame {
+            return None;
+        }
+        self.span_annotations
+            .iter()
+            .filter(|span| span.file == self.current_file_path)
+            .filter(|span| span.line_start <= line_num && line_num <= span.line_end)
+            .filter_map(|span| match span.kind {
+                SpanKind::Blame(rank) => Some(rank),
+                _ => None,
+            })
+            .min()
+    }
+
+    /// Shows the errors of the session in the navigator, with this error at
+    /// `session_index`, and gets the state ready to be (re-)run.
+    pub fn enter_session(&mut self, session_errors: Vec<SessionError>, session_index: usize) {
+        self.session_errors = session_errors;
+        self.session_index = session_index;
+        self.navigator_selection = session_index;
+        self.exit_intent = None;
+        self.mode = AppMode::Browsing;
+    }
+
+    /// Whether the annotation differs from the one the error was loaded with
+    /// (as far as the undo history knows).
+    pub fn is_modified(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// The status of the annotation as it currently is.
+    pub fn status(&self) -> ErrorStatus {
+        if self.fixes.iter().any(|fix| fix.has_trivial_fix == Some(true)) {
+            ErrorStatus::Trivial
+        } else if self.fixes.iter().any(|fix| !fix.is_empty()) {
+            ErrorStatus::HasFixes
+        } else {
+            ErrorStatus::Unannotated
+        }
+    }
+
+    fn open_navigator(&mut self) {
+        if !self.session_errors.is_empty() {
+            self.navigator_selection = self.session_index;
+            self.mode = AppMode::ErrorNavigator;
+        }
+    }
+
+    fn exit_navigator_mode(&mut self, go: bool) {
+        self.mode = AppMode::Browsing;
+        if go && self.navigator_selection != self.session_index {
+            self.exit_intent = Some(ExitIntent::JumpTo(self.navigator_selection));
+        }
+    }
+
+    /// The fix being edited
+    fn fix(&self) -> &FixAnnotation {
+        &self.fixes[self.current_fix]
+    }
+
+    fn fix_mut(&mut self) -> &mut FixAnnotation {
+        &mut self.fixes[self.current_fix]
+    }
+
+    fn annotation(&self) -> Annotation {
+        Annotation {
+            fixes: self.fixes.clone(),
+            current_fix: self.current_fix,
+        }
+    }
+
+    fn restore_annotation(&mut self, annotation: Annotation) {
+        self.fixes = annotation.fixes;
+        self.current_fix = annotation.current_fix.min(self.fixes.len() - 1);
+    }
+
+    /// Records `before` (the annotation before a change) in the undo history,
+    /// unless the change didn't change any fix.
+    fn record_change(&mut self, before: Annotation) {
+        if before.fixes != self.fixes {
+            self.undo_stack.push(before);
+            self.redo_stack.clear();
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(annotation) = self.undo_stack.pop() {
+            self.redo_stack.push(self.annotation());
+            self.restore_annotation(annotation);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(annotation) = self.redo_stack.pop() {
+            self.undo_stack.push(self.annotation());
+            self.restore_annotation(annotation);
+        }
+    }
+
+    fn switch_fix(&mut self, forward: bool) {
+        let num_fixes = self.fixes.len();
+        self.current_fix = if forward {
+            (self.current_fix + 1) % num_fixes
+        } else {
+            (self.current_fix + num_fixes - 1) % num_fixes
+        };
+        self.current_miss = None;
+    }
+
+    /// Adds a new fix (a copy of the current one if `duplicate`) and switches
+    /// to it.
+    fn add_fix(&mut self, duplicate: bool) {
+        let before = self.annotation();
+        let fix = if duplicate {
+            self.fix().clone()
+        } else {
+            FixAnnotation::default()
+        };
+        self.fixes.push(fix);
+        self.current_fix = self.fixes.len() - 1;
+        self.record_change(before);
+    }
+
+    /// Deletes the current fix. The last fix is cleared instead, so there is
+    /// always one to edit.
+    fn delete_fix(&mut self) {
+        let before = self.annotation();
+        if self.fixes.len() > 1 {
+            self.fixes.remove(self.current_fix);
+            self.current_fix = self.current_fix.min(self.fixes.len() - 1);
+        } else {
+            self.fixes[0] = FixAnnotation::default();
+        }
+        self.record_change(before);
+    }
+
     fn move_up(&mut self) {
         if self.current_line > 0 {
             self.current_line -= 1;
@@ -189,6 +753,83 @@ impl AppState {
         }
         let max_scroll_offset = self.lines.len().saturating_sub(vp_height);
         self.scroll_offset = self.scroll_offset.min(max_scroll_offset);
+        // Span labels take up rows too, which may push the cursor out of view
+        while self.scroll_offset < self.current_line
+            && (self.scroll_offset..self.current_line)
+                .map(|line_idx| 1 + self.span_rows(line_idx + 1).len())
+                .sum::<usize>()
+                >= vp_height
+        {
+            self.scroll_offset += 1;
+        }
+    }
+
+    /// The rows of span underlines and labels to draw under line `line_num`
+    /// (1-indexed) of the current file, rustc style: the underlines of all
+    /// spans ending on the line, the label of the rightmost one next to them,
+    /// and the other labels on rows of their own, right to left. Positions
+    /// are in characters from the start of the line.
+    fn span_rows(&self, line_num: usize) -> Vec<Vec<(usize, String, Style)>> {
+        let line_len = self.lines.get(line_num.wrapping_sub(1)).map_or(0, |line| line.chars().count());
+        // (start, end, span); multi-line spans are underlined to the end of
+        // their first line and from the indentation of their last line, with
+        // the label on the last one
+        let mut segments: Vec<(usize, usize, &SpanAnnotation)> = vec![];
+        for span in &self.span_annotations {
+            let is_shown = if span.kind.is_blame_overlay() {
+                self.show_blame
+            } else {
+                self.show_spans
+            };
+            if !is_shown || span.file != self.current_file_path || line_num < span.line_start || line_num > span.line_end {
+                continue;
+            }
+            let start = if line_num == span.line_start {
+                span.column_start.saturating_sub(1)
+            } else if line_num == span.line_end {
+                let line = &self.lines[line_num - 1];
+                line.chars().take_while(|c| c.is_whitespace()).count()
+            } else {
+                continue;
+            };
+            let end = if line_num == span.line_end {
+                span.column_end.saturating_sub(1)
+            } else {
+                line_len
+            };
+            segments.push((start, end.max(start + 1), span));
+        }
+        if segments.is_empty() {
+            return vec![];
+        }
+        segments.sort_by_key(|(start, _, _)| *start);
+
+        let mut underline = vec![];
+        for (start, end, span) in &segments {
+            underline.push((*start, span.marker().repeat(end - start), span.style()));
+        }
+        let mut labelled: Vec<(usize, String, Style)> = segments
+            .iter()
+            .filter(|(_, _, span)| span.line_end == line_num)
+            .filter_map(|(start, _, span)| Some((*start, span.label.clone()?, span.style())))
+            .collect();
+        let mut rows = vec![];
+        // The rightmost label goes next to the underlines if nothing follows it
+        let (last_start, last_end, _) = segments[segments.len() - 1];
+        if labelled.last().is_some_and(|(start, _, _)| *start == last_start) {
+            let (_, label, style) = labelled.pop().unwrap();
+            underline.push((last_end + 1, label, style));
+        }
+        rows.push(underline);
+        while let Some((start, label, style)) = labelled.pop() {
+            let mut row: Vec<(usize, String, Style)> = labelled
+                .iter()
+                .map(|(other_start, _, other_style)| (*other_start, "|".to_string(), *other_style))
+                .collect();
+            row.push((start, label, style));
+            rows.push(row);
+        }
+        rows
     }
 
     fn enter_edit_mode(&mut self) {
@@ -196,6 +837,7 @@ impl AppState {
         let line_loc = LineLoc::new(line_to_edit, self.current_file_path.clone());
         self.editing_line = Some(line_to_edit);
         let current_fix = self
+            .fix()
             .fix_lines
             .get(&line_loc)
             .cloned()
@@ -209,12 +851,14 @@ impl AppState {
         if let Some(line_num) = self.editing_line {
             let line_loc = LineLoc::new(line_num, self.current_file_path.clone());
             if save {
+                let before = self.annotation();
                 let fix_text = self.input.value().to_string();
                 if fix_text.is_empty() {
-                    self.fix_lines.insert(line_loc, None);
+                    self.fix_mut().fix_lines.insert(line_loc, None);
                 } else {
-                    self.fix_lines.insert(line_loc, Some(fix_text));
+                    self.fix_mut().fix_lines.insert(line_loc, Some(fix_text));
                 }
+                self.record_change(before);
             }
         }
         self.input.reset();
@@ -242,23 +886,25 @@ impl AppState {
     }
 
     fn exit_add_note_mode(&mut self, save_note: bool) {
+        let before = self.annotation();
         if save_note && !self.input.value().is_empty() {
-            self.note = Some(self.input.value().to_string());
+            self.fix_mut().note = Some(self.input.value().to_string());
         } else {
-            self.note = None;
+            self.fix_mut().note = None;
         }
+        self.record_change(before);
         self.initiate_save_and_next();
     }
 
     fn initiate_save_and_next(&mut self) {
-        let (confirmation_title, confirmation_message) = make_confirmation_message(&self.fix_lines);
+        let (confirmation_title, confirmation_message) = make_confirmation_message(&self.fixes);
         self.request_confirmation(
             confirmation_title,
             confirmation_message,
             AppMode::Browsing,
             |app_state, confirmed| {
                 if confirmed {
-                    app_state.exit_intent = Some(ExitIntent::SaveAndRedo);
+                    app_state.add_fix(false);
                 } else {
                     app_state.exit_intent = Some(ExitIntent::SaveAndNext);
                 }
@@ -270,7 +916,9 @@ impl AppState {
     fn clear_fix(&mut self) {
         let line_to_clear = self.current_line + 1;
         let line_loc = LineLoc::new(line_to_clear, self.current_file_path.clone());
-        self.fix_lines.remove(&line_loc);
+        let before = self.annotation();
+        self.fix_mut().fix_lines.remove(&line_loc);
+        self.record_change(before);
     }
 
     fn exit_file_explorer_mode(&mut self, go: bool) -> Result<()> {
@@ -368,27 +1016,14 @@ impl AppState {
         Ok(())
     }
 
-    pub fn fixes(&self, dir_path: &Path) -> Result<Fix> {
-        let fix_lines = self
-            .fix_lines
+    /// The fixes annotated in the editor. Empty fixes (no fix lines and no
+    /// note) are left out.
+    pub fn fixes(&self, dir_path: &Path) -> Result<Vec<Fix>> {
+        self.fixes
             .iter()
-            .map(|(line_loc, fix)| {
-                Ok::<FixLine, anyhow::Error>(FixLine {
-                    line: line_loc.line,
-                    // Make the lines relative to the directory we run in
-                    // This is the convention we adopt for saving other files.
-                    file: pathdiff::diff_paths(&line_loc.file, dir_path).ok_or_else(|| {
-                        anyhow::anyhow!("Couldn't diff path {:?} and {:?}", dir_path, line_loc.file)
-                    })?,
-                    added_reft: fix.clone(),
-                })
-            })
-            .collect::<Result<_>>()?;
-        Ok(Fix {
-            fix_lines,
-            note: self.note.clone(),
-            is_trivial: self.has_trivial_fix,
-        })
+            .filter(|fix| !fix.is_empty())
+            .map(|fix| fix.to_fix(dir_path))
+            .collect()
     }
 }
 
@@ -399,7 +1034,8 @@ pub fn run_app(
     loop {
         // Adjust scroll based on cursor position before drawing
         let viewport_height = terminal.size()?.height as usize;
-        let content_height = viewport_height.saturating_sub(2); // Subtract border heights
+        // Subtract border heights, the fix panel and the blame panel
+        let content_height = viewport_height.saturating_sub(2 + 3 + app_state.blame_panel_height() as usize);
         app_state.adjust_scroll(content_height.max(1)); // Ensure content_height > 0
 
         terminal.draw(|frame| ui(frame, app_state))?;
@@ -424,6 +1060,7 @@ pub fn run_app(
             AppMode::FileExplorer => handle_file_explorer_input(event, app_state)?,
             AppMode::ConfirmationDialog => handle_confirmation_dialog_input(event, app_state)?,
             AppMode::AddNote => handle_add_note_input(event, app_state)?,
+            AppMode::ErrorNavigator => handle_navigator_input(event, app_state)?,
         }
 
         if app_state.exit_intent.is_some() {
@@ -441,6 +1078,11 @@ fn handle_browsing_input(
     if let Event::Key(key) = event {
         if key.kind == KeyEventKind::Press {
             match key.code {
+                // Reviewing an eval run follows the misses, and only shows
+                // the annotation
+                KeyCode::Char('n') if app_state.reviews.is_some() => app_state.go_to_next_miss(true)?,
+                KeyCode::Char('N') if app_state.reviews.is_some() => app_state.go_to_next_miss(false)?,
+                code if app_state.reviews.is_some() && is_annotation_key(code) => {}
                 KeyCode::Char('q') | KeyCode::Esc => app_state.request_confirmation(
                     "Really quit?".to_string(),
                     None,
@@ -463,23 +1105,33 @@ fn handle_browsing_input(
                         Ok(())
                     },
                 ),
+                KeyCode::Char('D') | KeyCode::Delete => app_state.request_confirmation(
+                    "Really delete this benchmark?".to_string(),
+                    Some("It is removed from the suite when the session's changes are saved.".to_string()),
+                    AppMode::Browsing,
+                    |app_state, confirmed| {
+                        if confirmed {
+                            app_state.exit_intent = Some(ExitIntent::Delete);
+                        }
+                        Ok(())
+                    },
+                ),
                 KeyCode::Char('t') => app_state.request_confirmation(
                     "Does this error have a trivial fix?".to_string(),
                     Some("i.e. can you successfully copy the failing refinement onto the fix lines?".to_string()),
                     AppMode::Browsing,
                     |app_state, has_trivial_fix| {
-                        app_state.has_trivial_fix = Some(has_trivial_fix);
+                        let before = app_state.annotation();
+                        app_state.fix_mut().has_trivial_fix = Some(has_trivial_fix);
+                        app_state.record_change(before);
                         Ok(())
                     },
                 ),
                 KeyCode::Char('z') | KeyCode::Char('n') => {
                     app_state.mode = AppMode::AddNote;
                     // Populate the input field if a note is provided
-                    match &app_state.note {
-                        Some(note) => {
-                            app_state.input = Input::default().with_value(note.clone());
-                        }
-                        _ => {}
+                    if let Some(note) = &app_state.fix().note {
+                        app_state.input = Input::default().with_value(note.clone());
                     }
                 }
                 // Navigation
@@ -511,6 +1163,25 @@ fn handle_browsing_input(
                 KeyCode::Char('c') | KeyCode::Char('x') => {
                     app_state.clear_fix();
                 }
+                // Alternative fixes
+                KeyCode::Char(']') | KeyCode::Tab => app_state.switch_fix(true),
+                KeyCode::Char('[') | KeyCode::BackTab => app_state.switch_fix(false),
+                KeyCode::Char('a') => app_state.add_fix(false),
+                KeyCode::Char('y') => app_state.add_fix(true),
+                KeyCode::Char('X') => app_state.request_confirmation(
+                    "Really delete this fix?".to_string(),
+                    None,
+                    AppMode::Browsing,
+                    |app_state, confirmed| {
+                        if confirmed {
+                            app_state.delete_fix();
+                        }
+                        Ok(())
+                    },
+                ),
+                // Undo/redo (`u` is taken by paging)
+                KeyCode::Char('U') => app_state.undo(),
+                KeyCode::Char('R') => app_state.redo(),
                 KeyCode::Char('g') => {
                     app_state.mode = AppMode::GoToLine;
                 }
@@ -523,6 +1194,9 @@ fn handle_browsing_input(
                 KeyCode::Char('e') => {
                     app_state.next_error()?;
                 }
+                KeyCode::Char('l') => app_state.open_navigator(),
+                KeyCode::Char('v') => app_state.show_spans = !app_state.show_spans,
+                KeyCode::Char('b') => app_state.toggle_blame(),
                 _ => {} // Ignore other keys in browsing mode
             }
         }
@@ -530,6 +1204,16 @@ fn handle_browsing_input(
     Ok(())
 }
 
+/// Keys of the browsing mode that change the annotation.
+fn is_annotation_key(code: KeyCode) -> bool {
+    matches!(
+        code,
+        KeyCode::Enter
+            | KeyCode::Delete
+            | KeyCode::Char(' ' | 'c' | 'x' | 'a' | 'y' | 'X' | 'U' | 'R' | 't' | 'z' | 'n' | 'D')
+    )
+}
+
 fn handle_editing_input(event: Event, app_state: &mut AppState) -> Result<()> {
     if let Event::Key(key) = event {
         match key.code {
@@ -584,6 +1268,29 @@ fn handle_add_note_input(event: Event, app_state: &mut AppState) -> Result<()> {
     Ok(())
 }
 
+fn handle_navigator_input(event: Event, app_state: &mut AppState) -> Result<()> {
+    if let Event::Key(key) = event {
+        if key.kind == KeyEventKind::Press {
+            let num_errors = app_state.session_errors.len();
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    app_state.navigator_selection = app_state.navigator_selection.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    app_state.navigator_selection =
+                        (app_state.navigator_selection + 1).min(num_errors.saturating_sub(1));
+                }
+                KeyCode::Enter => app_state.exit_navigator_mode(true),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('l') => {
+                    app_state.exit_navigator_mode(false)
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
 fn handle_file_explorer_input(event: Event, app_state: &mut AppState) -> Result<()> {
     if let Event::Key(key) = event {
         match key.code {
@@ -642,8 +1349,18 @@ fn ui(frame: &mut Frame, app_state: &AppState) {
         .background
         .map_or(Color::Reset, |bg| Color::Rgb(bg.r, bg.g, bg.b));
 
-    // --- Main File View ---
-    render_file_view(frame, app_state, area, theme_bg);
+    // --- Main File View, with the fix switcher and blame panel underneath ---
+    let [file_area, fix_panel_area, blame_panel_area] = Layout::vertical([
+        Constraint::Min(3),
+        Constraint::Length(3),
+        Constraint::Length(app_state.blame_panel_height()),
+    ])
+    .areas(area);
+    render_file_view(frame, app_state, file_area, theme_bg);
+    render_fix_panel(frame, app_state, fix_panel_area);
+    if let Some(constraint_info) = app_state.constraint_info.as_ref().filter(|_| app_state.show_blame) {
+        render_blame_panel(frame, constraint_info, blame_panel_area);
+    }
 
     // --- Other dialogs ---
     match app_state.mode {
@@ -668,10 +1385,98 @@ fn ui(frame: &mut Frame, app_state: &AppState) {
                 render_confirmation_dialog(frame, confirm_state);
             }
         }
+        AppMode::ErrorNavigator => {
+            render_error_navigator(frame, app_state);
+        }
         _ => {}
     }
 }
 
+/// Backgrounds for the lines of each fix (by fix index, wrapping around).
+const FIX_COLORS: [Color; 6] = [
+    Color::Rgb(70, 38, 46),
+    Color::Rgb(38, 70, 46),
+    Color::Rgb(38, 50, 86),
+    Color::Rgb(80, 70, 30),
+    Color::Rgb(70, 38, 80),
+    Color::Rgb(30, 70, 80),
+];
+
+fn fix_color(fix_idx: usize) -> Color {
+    FIX_COLORS[fix_idx % FIX_COLORS.len()]
+}
+
+/// Renders a tab per fix, each in the colour its lines are marked with.
+fn render_fix_panel(frame: &mut Frame, app_state: &AppState, area: Rect) {
+    let mut spans = vec![];
+    for (fix_idx, fix) in app_state.fixes.iter().enumerate() {
+        let mut label = format!(" Fix {}: {} lines", fix_idx + 1, fix.fix_lines.len());
+        if let Some(review) = app_state.reviews.as_ref().and_then(|reviews| reviews.get(fix_idx)) {
+            label.push_str(&format!(
+                " ({} hit, {} by binders only, {} missed)",
+                review.num_hits(LineHit::BlameSpan),
+                review.num_hits(LineHit::AllBinders),
+                review.num_hits(LineHit::Missed)
+            ));
+        }
+        if let Some(is_trivial) = fix.has_trivial_fix {
+            label.push_str(if is_trivial { ", trivial" } else { ", non-trivial" });
+        }
+        if fix.note.is_some() {
+            label.push_str(", note");
+        }
+        label.push(' ');
+        let mut style = Style::default().fg(Color::White).bg(fix_color(fix_idx));
+        if fix_idx == app_state.current_fix {
+            style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+        }
+        spans.push(Span::styled(label, style));
+        spans.push(Span::raw(" "));
+    }
+    let title = if app_state.reviews.is_some() {
+        " Eval: green hit, yellow by binders only, red missed ([/] switch, n/N next/previous miss) "
+    } else {
+        " Fixes ([/] switch, a add, y duplicate, X delete) "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+/// Lists the failing constraint and the blame spans flux found, in rank order.
+fn render_blame_panel(frame: &mut Frame, constraint_info: &ConstraintDebugInfoDeserialize, area: Rect) {
+    let mut lines = vec![Line::from(vec![
+        Span::styled("Constraint: ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(constraint_info.constraint.clone()),
+    ])];
+    for (rank, blame) in constraint_info.blame_spans.iter().enumerate() {
+        let location = match &blame.blame_span {
+            Some(span) => format!("{}:{}", span.start.file, span.start.line),
+            None => "(no span)".to_string(),
+        };
+        let suggestion = blame
+            .suggested_refinement
+            .as_ref()
+            .map(|refinement| format!(" => {}", refinement))
+            .unwrap_or_default();
+        lines.push(Line::styled(
+            format!("#{} {} {}{}", rank + 1, location, blame.binder_name, suggestion),
+            Style::default().fg(blame_color(rank)),
+        ));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " Blame ({} spans, {} binders; b toggle) ",
+            constraint_info.blame_spans.len(),
+            constraint_info.binders.len()
+        ))
+        .border_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
 fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_bg: Color) {
     // --- Syntax Highlighting Setup ---
     let syntax = app_state
@@ -694,10 +1499,12 @@ fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_b
     let mut text_lines: Vec<Line> = Vec::new();
     let display_height = area.height.saturating_sub(2) as usize; // Minus borders
     let line_number_width = app_state.lines.len().to_string().len().max(3) as u16; // Ensure min width
-    let display_width = area.width.saturating_sub(3 + line_number_width); // Minus borders & line num space
+    // With several fixes, the gutter has a marker column per fix
+    let num_markers = if app_state.fixes.len() > 1 { app_state.fixes.len() } else { 0 };
+    let display_width = area.width.saturating_sub(3 + line_number_width + num_markers as u16); // Minus borders, line num & marker space
 
     let highlight_bg = Color::Rgb(35, 38, 46); // Current line highlight
-    let fix_line_bg = Color::Rgb(70, 38, 46); // Background for lines with fixes
+    let fix_line_bg = fix_color(app_state.current_fix); // Background for lines of the current fix
 
     let visible_lines = app_state
         .lines
@@ -710,7 +1517,7 @@ fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_b
         let line_num = line_idx + 1; // 1-based for display and map keys
         let line_loc = LineLoc::new(line_num, app_state.current_file_path.clone());
         let is_current_line = line_idx == app_state.current_line;
-        let has_fix = app_state.fix_lines.contains_key(&line_loc);
+        let has_fix = app_state.fix().fix_lines.contains_key(&line_loc);
 
         // Determine line background
         let mut line_bg = if is_current_line {
@@ -718,24 +1525,36 @@ fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_b
         } else {
             theme_bg
         };
-        if has_fix {
+        if let Some(line_hit) = app_state.line_hit(&line_loc) {
+            line_bg = line_hit.color();
+        } else if has_fix {
             line_bg = fix_line_bg;
             // Optionally blend if it's also the current line
             // if is_current_line { line_bg = blend_colors(highlight_bg, fix_line_bg); }
         }
 
-        // 1. Line Number Span
-        let line_num_style = Style::default()
-            .fg(if is_current_line {
-                Color::White
-            } else {
-                Color::DarkGray
-            })
-            .bg(line_bg); // Use calculated line_bg
+        // 1. Line Number Span, coloured by the best blame span on the line
+        let line_num_fg = match app_state.blame_rank(line_num) {
+            Some(rank) => blame_color(rank),
+            None if is_current_line => Color::White,
+            None => Color::DarkGray,
+        };
+        let line_num_style = Style::default().fg(line_num_fg).bg(line_bg); // Use calculated line_bg
         let line_num_span = Span::styled(
             format!("{:>width$} ", line_num, width = line_number_width as usize),
             line_num_style,
         );
+        // Mark the line for every fix it is part of, so fixes can be compared
+        let marker_spans = (0..num_markers).map(|fix_idx| {
+            if app_state.fixes[fix_idx].fix_lines.contains_key(&line_loc) {
+                Span::styled(
+                    ((fix_idx + 1) % 10).to_string(),
+                    Style::default().fg(Color::White).bg(fix_color(fix_idx)),
+                )
+            } else {
+                Span::styled(" ", Style::default().bg(line_bg))
+            }
+        });
 
         // 2. Content Spans
         let ranges: Vec<(syntect::highlighting::Style, &str)> = highlighter
@@ -764,11 +1583,41 @@ fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_b
 
         // 4. Combine Spans into a Line
         let mut all_spans = vec![line_num_span];
+        all_spans.extend(marker_spans);
         all_spans.extend(content_spans);
         all_spans.push(padding_span);
         let line = Line::from(all_spans);
         text_lines.push(line);
+
+        // 5. Span underlines and labels as virtual lines under the source
+        let gutter = " ".repeat(line_number_width as usize + 1 + num_markers);
+        for row in app_state.span_rows(line_num) {
+            let mut row_spans = vec![Span::styled(gutter.clone(), Style::default().bg(theme_bg))];
+            let mut column = 0;
+            for (start, text, style) in row {
+                match start.cmp(&column) {
+                    std::cmp::Ordering::Greater => {
+                        row_spans.push(Span::styled(" ".repeat(start - column), Style::default().bg(theme_bg)));
+                    }
+                    std::cmp::Ordering::Less => {
+                        // Overlaps what is already drawn, so it's pushed to the right
+                        row_spans.push(Span::styled(" ", Style::default().bg(theme_bg)));
+                        column += 1;
+                    }
+                    std::cmp::Ordering::Equal => {}
+                }
+                column = column.max(start) + text.chars().count();
+                row_spans.push(Span::styled(text, style.bg(theme_bg)));
+            }
+            let padding_needed = (display_width as usize).saturating_sub(column);
+            row_spans.push(Span::styled(" ".repeat(padding_needed), Style::default().bg(theme_bg)));
+            text_lines.push(Line::from(row_spans));
+        }
+        if text_lines.len() >= display_height {
+            break;
+        }
     }
+    text_lines.truncate(display_height);
 
     // Fill remaining vertical space if content is shorter than display height
     while text_lines.len() < display_height {
@@ -778,7 +1627,7 @@ fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_b
             line_num_style,
         );
         let padding_span = Span::styled(
-            " ".repeat(display_width as usize),
+            " ".repeat(display_width as usize + num_markers),
             Style::default().bg(theme_bg),
         );
         text_lines.push(Line::from(vec![line_num_span, padding_span]));
@@ -786,7 +1635,7 @@ fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_b
 
     // --- Create Main Widget ---
     let title = format!(
-        " File: {} | Line {}/{} | Offset {} | Mode: {:?} ",
+        " File: {} | Line {}/{} | Offset {} | Fix {}/{} | Changes {} (U undo, R redo) | Mode: {:?} ",
         app_state
             .current_file_path
             .file_name()
@@ -795,6 +1644,9 @@ fn render_file_view(frame: &mut Frame, app_state: &AppState, area: Rect, theme_b
         app_state.current_line + 1,
         app_state.lines.len(),
         app_state.scroll_offset,
+        app_state.current_fix + 1,
+        app_state.fixes.len(),
+        app_state.undo_stack.len(),
         app_state.mode,
     );
     let block = Block::default()
@@ -949,6 +1801,46 @@ fn render_file_explorer(frame: &mut Frame, app_state: &AppState) {
     frame.render_widget(&app_state.file_explorer.widget(), frame.area());
 }
 
+fn render_error_navigator(frame: &mut Frame, app_state: &AppState) {
+    let area = frame.area();
+    let width = app_state
+        .session_errors
+        .iter()
+        .map(|error| error.error_name.len() + 20)
+        .max()
+        .unwrap_or(0)
+        .clamp(30, area.width as usize) as u16;
+    let height = (app_state.session_errors.len() as u16 + 2).min(area.height);
+    let popup_area = centered_rect_abs(width, height, area);
+
+    let items: Vec<ListItem> = app_state
+        .session_errors
+        .iter()
+        .enumerate()
+        .map(|(idx, error)| {
+            let marker = if idx == app_state.session_index { "> " } else { "  " };
+            let modified = if error.is_modified { "*" } else { " " };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{}{}{} ", marker, modified, error.error_name)),
+                Span::styled(format!("[{}]", error.status), Style::default().fg(error.status.color())),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Errors (Enter = Go, Esc = Cancel, * = unsaved) ")
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .style(Style::default().bg(Color::DarkGray))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut list_state = ListState::default().with_selected(Some(app_state.navigator_selection));
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
+
 fn render_confirmation_dialog(frame: &mut Frame, confirm_state: &ConfirmationState) {
     let area = frame.area();
     // Define dialog size (e.g., 60% width, fixed height or based on text)
@@ -1048,61 +1940,58 @@ fn centered_rect_abs(width: u16, height: u16, r: Rect) -> Rect {
 /// Generates a summary title and body message confirming proposed fixes. The
 /// body is empty if there are no fixes.
 ///
-/// Groups fixes by file and counts total fixes and refinements per file.
+/// Groups the lines of each fix by file and counts total fixes and
+/// refinements per file.
 ///
 /// # Arguments
 ///
-/// * `fix_lines` - A map where keys are `LineLoc` (line number and file path)
-///                 and values are `Option<String>`, representing an optional
-///                 refinement suggestion (`Some`) or just a fix (`None`).
+/// * `fixes` - The alternative fixes. Their `fix_lines` map `LineLoc`s (line
+///             number and file path) to an optional refinement suggestion
+///             (`Some`) or just a fix (`None`).
 ///
-fn make_confirmation_message(
-    fix_lines: &BTreeMap<LineLoc, Option<String>>,
-) -> (String, Option<String>) {
-    // 1. Aggregate stats per file
-    //    Key: PathBuf (file path)
-    //    Value: (total_fixes_in_file, refinement_count_in_file)
-    let mut file_stats: BTreeMap<PathBuf, (usize, usize)> = BTreeMap::new();
-
-    for (line_loc, refinement_opt) in fix_lines.iter() {
-        let stats = file_stats.entry(line_loc.file.clone()).or_insert((0, 0));
-        stats.0 += 1; // Increment total fix count for this file
-        if refinement_opt.is_some() {
-            stats.1 += 1; // Increment refinement count if Some(String)
+fn make_confirmation_message(fixes: &[FixAnnotation]) -> (String, Option<String>) {
+    let title = "Add a different set of fixes for this error?".to_string();
+    let mut message = String::new();
+
+    for (fix_idx, fix) in fixes.iter().enumerate() {
+        // 1. Aggregate stats per file
+        //    Key: PathBuf (file path)
+        //    Value: (total_fixes_in_file, refinement_count_in_file)
+        let mut file_stats: BTreeMap<PathBuf, (usize, usize)> = BTreeMap::new();
+        for (line_loc, refinement_opt) in fix.fix_lines.iter() {
+            let stats = file_stats.entry(line_loc.file.clone()).or_insert((0, 0));
+            stats.0 += 1; // Increment total fix count for this file
+            if refinement_opt.is_some() {
+                stats.1 += 1; // Increment refinement count if Some(String)
+            }
+        }
+        if file_stats.is_empty() {
+            continue;
         }
-    }
-
-    // 2. Calculate totals
-    let total_fixes = fix_lines.len();
-    let total_files = file_stats.len();
-
-    let title = "Add a different set of fixes for this file?".to_string();
-
-    // 3. Build the output string
-    let mut message = format!("Summary: {} fixes in {} files\n", total_fixes, total_files);
-
-    // 4. Sort files alphabetically for consistent output
-    let mut sorted_stats: Vec<_> = file_stats.into_iter().collect();
-    sorted_stats.sort_by(|(path_a, _), (path_b, _)| path_a.cmp(path_b));
-    if sorted_stats.is_empty() {
-        return (title, None);
-    }
 
-    // 5. Add per-file details
-    for (file_path, (fixes, refinements)) in sorted_stats {
-        // Use path.display() for a printable representation of the path
+        // 2. Summarize the fix (the BTreeMap keeps files sorted)
         writeln!(
             &mut message,
-            "   * {}: {} fixes ({} refinements)",
-            file_path.display(),
-            fixes,
-            refinements
+            "Fix {}: {} fixes in {} files",
+            fix_idx + 1,
+            fix.fix_lines.len(),
+            file_stats.len()
         )
         .unwrap();
+        for (file_path, (fixes, refinements)) in file_stats {
+            writeln!(
+                &mut message,
+                "   * {}: {} fixes ({} refinements)",
+                file_path.display(),
+                fixes,
+                refinements
+            )
+            .unwrap();
+        }
     }
 
-    // Remove the trailing newline added by the last writeln! if you don't want it
-    // message.pop(); // Optional: depends if you want a trailing newline
-
+    if message.is_empty() {
+        return (title, None);
+    }
     (title, Some(message))
 }
@@ -19,15 +19,26 @@ impl LineLoc {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A range of columns on a single line. 1-indexed, inclusive on both ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FixLine {
     pub line: usize,
     /// This should be relative to the error run_dir
     pub file: PathBuf,
     pub added_reft: Option<String>,
+    /// The columns the fix applies to, if known. Only used by column-precise
+    /// evaluation; without it the whole line counts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<ColumnRange>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Fix {
     pub fix_lines: Vec<FixLine>,
     pub note: Option<String>,
@@ -46,6 +57,114 @@ pub struct ErrorAndFixes {
     pub fixes: Vec<Fix>,
     /// These line locations must be relative to the repo root
     pub error_lines: VecDeque<LineLoc>,
+    /// Identifies the error independently of its line number and of the order
+    /// flux emits errors in (see `error_fingerprint`). Missing for benchmarks
+    /// created before fingerprints existed; use `ErrorAndFixes::fingerprint`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+impl ErrorAndFixes {
+    /// Returns the stored fingerprint, or computes it for older benchmarks
+    /// (recovering the function name from the error name).
+    pub fn fingerprint(&self) -> Option<String> {
+        self.fingerprint.clone().or_else(|| {
+            let fn_name = fn_name_from_error_name(&self.error_name)?;
+            Some(error_fingerprint(fn_name, &self.error))
+        })
+    }
+}
+
+/// Extracts `fn` from an error name of the form `{short_hash}-{fn}-L{line}-{n}`.
+fn fn_name_from_error_name(error_name: &str) -> Option<&str> {
+    let (_short_hash, rest) = error_name.split_once('-')?;
+    let (rest, _n) = rest.rsplit_once('-')?;
+    let (fn_name, line) = rest.rsplit_once("-L")?;
+    line.parse::<usize>().ok()?;
+    Some(fn_name)
+}
+
+/// Computes a fingerprint of an error from the name of the function containing
+/// it, its message, the labels of its (and its children's) spans and the
+/// highlighted source text with whitespace normalized. Line numbers are left
+/// out so the fingerprint survives code being added above the error.
+pub fn error_fingerprint(fn_name: &str, error: &CompilerMessage) -> String {
+    let mut parts = vec![fn_name.to_string(), error.message.message.clone()];
+    let spans = error
+        .message
+        .spans
+        .iter()
+        .chain(error.message.children.iter().flat_map(|child| &child.spans));
+    for span in spans {
+        parts.push(span.label.clone().unwrap_or_default());
+        let snippet = span
+            .text
+            .iter()
+            .map(|highlight| {
+                highlight
+                    .text
+                    .chars()
+                    .skip(highlight.highlight_start.saturating_sub(1))
+                    .take(highlight.highlight_end.saturating_sub(highlight.highlight_start))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        parts.push(snippet.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    // FNV-1a, since std's hashers aren't guaranteed to be stable across
+    // releases and fingerprints are saved to disk.
+    let hash = parts.join("\u{0}").bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Matches `new` errors (e.g. from a fresh flux run) with `existing` ones
+/// (e.g. benchmarks), one to one. Errors are matched by fingerprint where the
+/// fingerprint is unambiguous, and by name otherwise.
+///
+/// # Returns
+/// * For each error in `new`, the index of its match in `existing`, if any.
+pub fn match_errors(new: &[ErrorAndFixes], existing: &[ErrorAndFixes]) -> Vec<Option<usize>> {
+    let new_fingerprints: Vec<Option<String>> = new.iter().map(|e| e.fingerprint()).collect();
+    let existing_fingerprints: Vec<Option<String>> = existing.iter().map(|e| e.fingerprint()).collect();
+    let is_unique = |fingerprints: &[Option<String>], fingerprint: &String| {
+        fingerprints.iter().filter(|f| f.as_ref() == Some(fingerprint)).count() == 1
+    };
+
+    let mut matches = vec![None; new.len()];
+    let mut used = vec![false; existing.len()];
+
+    // 1. Fingerprints that identify exactly one error on both sides
+    for (new_idx, fingerprint) in new_fingerprints.iter().enumerate() {
+        let Some(fingerprint) = fingerprint else { continue };
+        if !is_unique(&new_fingerprints, fingerprint) || !is_unique(&existing_fingerprints, fingerprint) {
+            continue;
+        }
+        if let Some(existing_idx) = existing_fingerprints
+            .iter()
+            .position(|f| f.as_ref() == Some(fingerprint))
+        {
+            matches[new_idx] = Some(existing_idx);
+            used[existing_idx] = true;
+        }
+    }
+
+    // 2. Fall back to the name for the rest
+    for (new_idx, error) in new.iter().enumerate() {
+        if matches[new_idx].is_some() {
+            continue;
+        }
+        if let Some(existing_idx) =
+            (0..existing.len()).find(|&idx| !used[idx] && existing[idx].error_name == error.error_name)
+        {
+            matches[new_idx] = Some(existing_idx);
+            used[existing_idx] = true;
+        }
+    }
+
+    matches
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -295,3 +414,131 @@ fn convert_ssh_to_https(url_str: &str) -> String {
     // If none of the patterns matched, return the original URL
     trimmed_url.to_string()
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// An error with the given name and message and no spans or fixes.
+    pub(crate) fn error_and_fixes(error_name: &str, message: &str) -> ErrorAndFixes {
+        let error = serde_json::from_value(serde_json::json!({
+            "reason": "compiler-message",
+            "package_id": "test 0.1.0",
+            "manifest_path": "Cargo.toml",
+            "target": {
+                "kind": ["lib"],
+                "crate_types": ["lib"],
+                "name": "test",
+                "src_path": "src/lib.rs",
+                "edition": "2021",
+                "doc": true,
+                "doctest": true,
+                "test": true,
+            },
+            "message": {
+                "message": message,
+                "code": null,
+                "level": "error",
+                "spans": [],
+                "children": [],
+                "rendered": null,
+                "$message_type": "diagnostic",
+            },
+        }))
+        .unwrap();
+        ErrorAndFixes {
+            error_name: error_name.to_string(),
+            error,
+            fixes: vec![],
+            error_lines: VecDeque::new(),
+            fingerprint: None,
+        }
+    }
+
+    fn with_fingerprint(mut error: ErrorAndFixes, fingerprint: &str) -> ErrorAndFixes {
+        error.fingerprint = Some(fingerprint.to_string());
+        error
+    }
+
+    #[test]
+    fn fingerprint_ignores_the_line_in_the_error_name() {
+        let old = error_and_fixes("abc1234-foo-L10-0", "refinement type error");
+        let moved = error_and_fixes("def5678-foo-L25-0", "refinement type error");
+        let other_fn = error_and_fixes("def5678-bar-L25-0", "refinement type error");
+
+        assert!(old.fingerprint().is_some());
+        assert_eq!(old.fingerprint(), moved.fingerprint());
+        assert_ne!(old.fingerprint(), other_fn.fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_prefers_the_stored_one() {
+        let error = with_fingerprint(error_and_fixes("abc1234-foo-L10-0", "error"), "stored");
+        assert_eq!(error.fingerprint().as_deref(), Some("stored"));
+        // Names that don't follow the naming scheme have no fingerprint
+        assert_eq!(error_and_fixes("custom", "error").fingerprint(), None);
+    }
+
+    #[test]
+    fn match_errors_matches_renamed_errors_by_fingerprint() {
+        let existing = vec![
+            with_fingerprint(error_and_fixes("a", "error"), "1"),
+            with_fingerprint(error_and_fixes("b", "error"), "2"),
+        ];
+        let new = vec![
+            with_fingerprint(error_and_fixes("renamed-b", "error"), "2"),
+            with_fingerprint(error_and_fixes("renamed-a", "error"), "1"),
+        ];
+        assert_eq!(match_errors(&new, &existing), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn match_errors_prefers_the_fingerprint_over_the_name() {
+        // `a`'s fingerprint now belongs to the error named `b`, and the new `a`
+        // is a different error that takes over the old `b`'s name match
+        let existing = vec![
+            with_fingerprint(error_and_fixes("a", "error"), "1"),
+            with_fingerprint(error_and_fixes("b", "error"), "2"),
+        ];
+        let new = vec![
+            with_fingerprint(error_and_fixes("a", "error"), "3"),
+            with_fingerprint(error_and_fixes("b", "error"), "1"),
+        ];
+        assert_eq!(match_errors(&new, &existing), vec![None, Some(0)]);
+    }
+
+    #[test]
+    fn match_errors_falls_back_to_the_name() {
+        let existing = vec![
+            error_and_fixes("a", "error"),
+            with_fingerprint(error_and_fixes("b", "error"), "2"),
+        ];
+        let new = vec![
+            with_fingerprint(error_and_fixes("b", "error"), "changed"),
+            error_and_fixes("a", "error"),
+            error_and_fixes("c", "error"),
+        ];
+        assert_eq!(match_errors(&new, &existing), vec![Some(1), Some(0), None]);
+    }
+
+    #[test]
+    fn match_errors_ignores_ambiguous_fingerprints() {
+        // The fingerprint is shared on the new side, so only the names match
+        let existing = vec![
+            with_fingerprint(error_and_fixes("a", "error"), "1"),
+            with_fingerprint(error_and_fixes("b", "error"), "2"),
+        ];
+        let new = vec![
+            with_fingerprint(error_and_fixes("b", "error"), "1"),
+            with_fingerprint(error_and_fixes("c", "error"), "1"),
+        ];
+        assert_eq!(match_errors(&new, &existing), vec![Some(1), None]);
+    }
+
+    #[test]
+    fn match_errors_matches_one_to_one() {
+        let existing = vec![error_and_fixes("a", "error")];
+        let new = vec![error_and_fixes("a", "error"), error_and_fixes("a", "error")];
+        assert_eq!(match_errors(&new, &existing), vec![Some(0), None]);
+    }
+}
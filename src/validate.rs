@@ -0,0 +1,211 @@
+use crate::benchmark_suite::BenchmarkSuite;
+use crate::types::{ErrorAndFixes, LineLoc};
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Which kind of line a line location of a benchmark is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    FixLine,
+    ErrorLine,
+}
+
+impl fmt::Display for LineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineKind::FixLine => write!(f, "Fix line"),
+            LineKind::ErrorLine => write!(f, "Error line"),
+        }
+    }
+}
+
+/// An inconsistency in a benchmark suite.
+#[derive(Debug, Clone)]
+pub enum Problem {
+    /// The suite has no `git-info.json`, so its source can't be checked out
+    MissingGitInfo,
+    /// The benchmark file can't be read or parsed
+    UnreadableBenchmark(String),
+    /// The benchmark file isn't named `<error_name>.json`
+    FileNameMismatch { error_name: String },
+    /// Another benchmark file in the suite has the same error name
+    DuplicateErrorName { error_name: String, other_file: PathBuf },
+    /// The file of a line doesn't exist in the worktree
+    MissingFile { kind: LineKind, loc: LineLoc },
+    /// The line is past the end of its file
+    LineBeyondEof { kind: LineKind, loc: LineLoc, num_lines: usize },
+    /// An error line points at a blank line
+    BlankErrorLine { loc: LineLoc },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::MissingGitInfo => write!(f, "Missing git-info.json"),
+            Problem::UnreadableBenchmark(e) => write!(f, "Unreadable benchmark: {}", e),
+            Problem::FileNameMismatch { error_name } => {
+                write!(f, "File name doesn't match error_name {}", error_name)
+            }
+            Problem::DuplicateErrorName { error_name, other_file } => write!(
+                f,
+                "Error name {} is also used by {}",
+                error_name,
+                other_file.display()
+            ),
+            Problem::MissingFile { kind, loc } => write!(
+                f,
+                "{} {}:{} is in a file that doesn't exist",
+                kind,
+                loc.file.display(),
+                loc.line
+            ),
+            Problem::LineBeyondEof { kind, loc, num_lines } => write!(
+                f,
+                "{} {}:{} is beyond the end of the file ({} lines)",
+                kind,
+                loc.file.display(),
+                loc.line,
+                num_lines
+            ),
+            Problem::BlankErrorLine { loc } => write!(
+                f,
+                "Error line {}:{} is blank",
+                loc.file.display(),
+                loc.line
+            ),
+        }
+    }
+}
+
+/// A problem found in a suite, with where it was found.
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub suite_path: PathBuf,
+    /// The benchmark file the problem is in, if any
+    pub benchmark_file: Option<PathBuf>,
+    pub problem: Problem,
+}
+
+/// Checks the suite's files without looking at the source: `git-info.json`
+/// exists, every benchmark file parses, is named after its error and has a
+/// unique error name.
+pub fn validate_suite_files(suite: &BenchmarkSuite) -> Result<Vec<ValidationIssue>> {
+    let issue = |benchmark_file: Option<&Path>, problem| ValidationIssue {
+        suite_path: suite.path().to_path_buf(),
+        benchmark_file: benchmark_file.map(Path::to_path_buf),
+        problem,
+    };
+    let mut issues = vec![];
+    if suite.git_info().is_none() {
+        issues.push(issue(None, Problem::MissingGitInfo));
+    }
+
+    let mut files_by_name: HashMap<String, PathBuf> = HashMap::new();
+    for path in suite.benchmark_files()? {
+        let benchmark = match BenchmarkSuite::read_benchmark_file(&path) {
+            Ok(benchmark) => benchmark,
+            Err(e) => {
+                issues.push(issue(Some(&path), Problem::UnreadableBenchmark(format!("{:#}", e))));
+                continue;
+            }
+        };
+        let error_name = benchmark.error_name;
+        if path.file_stem().and_then(|stem| stem.to_str()) != Some(error_name.as_str()) {
+            issues.push(issue(
+                Some(&path),
+                Problem::FileNameMismatch { error_name: error_name.clone() },
+            ));
+        }
+        // Files are sorted, so the later file is the one reported
+        if let Some(other_file) = files_by_name.get(&error_name) {
+            issues.push(issue(
+                Some(&path),
+                Problem::DuplicateErrorName {
+                    error_name: error_name.clone(),
+                    other_file: other_file.clone(),
+                },
+            ));
+        } else {
+            files_by_name.insert(error_name, path);
+        }
+    }
+    Ok(issues)
+}
+
+/// Checks the line locations of the suite's benchmarks against the source in
+/// `run_dir` (the directory flux runs in, which they are relative to).
+/// Unreadable benchmark files are skipped; `validate_suite_files` reports them.
+pub fn validate_suite_source(suite: &BenchmarkSuite, run_dir: &Path) -> Result<Vec<ValidationIssue>> {
+    let mut issues = vec![];
+    // The lines of each file, or `None` if it doesn't exist
+    let mut sources: HashMap<PathBuf, Option<Vec<String>>> = HashMap::new();
+    for path in suite.benchmark_files()? {
+        let Ok(benchmark) = BenchmarkSuite::read_benchmark_file(&path) else {
+            continue;
+        };
+        for (kind, loc) in line_locs(&benchmark) {
+            let lines = sources.entry(loc.file.clone()).or_insert_with(|| {
+                fs::read_to_string(run_dir.join(&loc.file))
+                    .ok()
+                    .map(|content| content.lines().map(str::to_string).collect())
+            });
+            let problem = match lines {
+                None => Some(Problem::MissingFile { kind, loc }),
+                Some(lines) if loc.line == 0 || loc.line > lines.len() => {
+                    Some(Problem::LineBeyondEof { kind, loc, num_lines: lines.len() })
+                }
+                Some(lines) if kind == LineKind::ErrorLine && lines[loc.line - 1].trim().is_empty() => {
+                    Some(Problem::BlankErrorLine { loc })
+                }
+                Some(_) => None,
+            };
+            if let Some(problem) = problem {
+                issues.push(ValidationIssue {
+                    suite_path: suite.path().to_path_buf(),
+                    benchmark_file: Some(path.clone()),
+                    problem,
+                });
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Every fix line and error line of `benchmark`.
+fn line_locs(benchmark: &ErrorAndFixes) -> Vec<(LineKind, LineLoc)> {
+    let fix_lines = benchmark.fixes.iter().flat_map(|fix| &fix.fix_lines).map(|fix_line| {
+        (LineKind::FixLine, LineLoc::new(fix_line.line, fix_line.file.clone()))
+    });
+    let error_lines = benchmark
+        .error_lines
+        .iter()
+        .map(|loc| (LineKind::ErrorLine, loc.clone()));
+    fix_lines.chain(error_lines).collect()
+}
+
+/// Generates a report of the issues, grouped by suite and benchmark file.
+pub fn generate_validation_report(issues: &[ValidationIssue]) -> String {
+    let mut sorted: Vec<&ValidationIssue> = issues.iter().collect();
+    sorted.sort_by(|a, b| (&a.suite_path, &a.benchmark_file).cmp(&(&b.suite_path, &b.benchmark_file)));
+
+    let mut report = format!("--- Validation Report ({} Issues) ---", issues.len());
+    let mut current_suite = None;
+    for issue in sorted {
+        if current_suite != Some(&issue.suite_path) {
+            report.push_str(&format!("\n{}", issue.suite_path.display()));
+            current_suite = Some(&issue.suite_path);
+        }
+        match &issue.benchmark_file {
+            Some(file) => {
+                let file_name = file.file_name().unwrap_or(file.as_os_str());
+                report.push_str(&format!("\n  {}: {}", file_name.to_string_lossy(), issue.problem));
+            }
+            None => report.push_str(&format!("\n  {}", issue.problem)),
+        }
+    }
+    report
+}